@@ -0,0 +1,119 @@
+use std::io::{self, BufRead, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::error::{EcError, Result};
+use crate::solver::{self, SolverLimits, SolverStatus};
+
+/// Where an answer to submit comes from
+pub enum AnswerSource {
+    /// Given directly on the command line
+    Literal(String),
+    /// Read from standard input (`-`)
+    Stdin,
+    /// Read from a file
+    File(String),
+    /// Captured from the stdout of a shell command, run under limits
+    Command(String, SolverLimits),
+}
+
+impl AnswerSource {
+    /// Whether the answer consumes stdin, so it can't be used for prompts
+    pub fn uses_stdin(&self) -> bool {
+        matches!(self, AnswerSource::Stdin)
+    }
+
+    /// Read the raw (unnormalized) answer
+    pub fn read(&self) -> Result<String> {
+        match self {
+            AnswerSource::Literal(answer) => Ok(answer.clone()),
+            AnswerSource::Stdin => {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                Ok(buffer)
+            }
+            AnswerSource::File(path) => Ok(std::fs::read_to_string(path)?),
+            AnswerSource::Command(command, limits) => {
+                let mut process = Command::new("sh");
+                // The solver runs in its own process group, so it must not read the terminal
+                process
+                    .arg("-c")
+                    .arg(command)
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::inherit());
+
+                let output = solver::run_limited(process, limits)?;
+                match output.status {
+                    SolverStatus::Success => Ok(output.stdout),
+                    SolverStatus::TimedOut => Err(EcError::SolverTimedOut {
+                        timeout: limits.timeout.unwrap_or_default(),
+                    }),
+                    status => Err(EcError::InvalidAnswer(format!("solver command {status}"))),
+                }
+            }
+        }
+    }
+}
+
+/// Normalize an answer before submission
+///
+/// Collapses CRLF line endings and trims surrounding whitespace. Empty and
+/// multi-line answers are rejected, since the server only accepts one line.
+pub fn normalize_answer(raw: &str) -> Result<String> {
+    let answer = raw.replace("\r\n", "\n").trim().to_string();
+
+    if answer.is_empty() {
+        return Err(EcError::InvalidAnswer("answer is empty".to_string()));
+    }
+    if answer.contains('\n') {
+        let lines = answer.lines().count();
+        return Err(EcError::InvalidAnswer(format!(
+            "answer spans {lines} lines, expected a single line"
+        )));
+    }
+
+    Ok(answer)
+}
+
+/// Ask for a line of input on the terminal, returned trimmed
+pub fn prompt(prompt: &str) -> Result<String> {
+    eprint!("{prompt} ");
+    io::stderr().flush()?;
+
+    let mut reply = String::new();
+    io::stdin().lock().read_line(&mut reply)?;
+
+    Ok(reply.trim().to_string())
+}
+
+/// Ask for confirmation on the terminal, defaulting to "no"
+pub fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{prompt} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut reply = String::new();
+    io::stdin().lock().read_line(&mut reply)?;
+
+    Ok(matches!(reply.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_trims_and_collapses_crlf() {
+        assert_eq!(normalize_answer("  42 \r\n").unwrap(), "42");
+        assert_eq!(normalize_answer("Drakzyph\n").unwrap(), "Drakzyph");
+    }
+
+    #[test]
+    fn test_normalize_rejects_empty() {
+        assert!(normalize_answer(" \r\n\t").is_err());
+    }
+
+    #[test]
+    fn test_normalize_rejects_multi_line() {
+        let err = normalize_answer("12\r\n34\r\n").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid answer: answer spans 2 lines, expected a single line");
+    }
+}
@@ -0,0 +1,611 @@
+//! Self-contained HTML archive of a year's descriptions
+//!
+//! Turns stored description fragments into a small static site: an
+//! `index.html` listing the quests and one `qNN.html` page per quest, each
+//! with the stylesheet embedded. Images and other referenced files are meant
+//! to be downloaded next to the pages (see [`asset_urls`] and [`asset_path`]);
+//! [`Site`] then points every reference at the local copy, so the archive
+//! keeps working without the live site. [`build`] does all of this for the
+//! descriptions in [`Storage`].
+//!
+//! Pages are re-serialized from the parsed DOM, which drops scripts and lets
+//! links to other archived quests become relative links between pages.
+//!
+//! Assets are found in `src`-like attributes, every candidate of a `srcset`,
+//! and `url(...)` in `style` attributes and `<style>` elements. References
+//! inside downloaded stylesheets and `@import` rules without `url(...)` are not
+//! followed, so those still load from the live site.
+//!
+//! Only built with the `cli` feature.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use ego_tree::NodeRef;
+use log::warn;
+use regex::{Captures, Regex};
+use reqwest::Url;
+use scraper::{Html, Node};
+
+use crate::client::{EcClient, BASE_URL};
+use crate::display;
+use crate::error::{EcError, Result};
+use crate::storage::Storage;
+
+/// Stylesheet embedded in every page
+pub const STYLESHEET: &str = r#"
+:root { color-scheme: light dark; --fg: #1d1f21; --bg: #fafafa; --muted: #6a6f75; --accent: #7a3e9d; --code-bg: #eceff1; }
+@media (prefers-color-scheme: dark) {
+  :root { --fg: #d8dee9; --bg: #1b1d23; --muted: #8b929c; --accent: #c792ea; --code-bg: #262a33; }
+}
+body { margin: 0 auto; max-width: 52rem; padding: 1.5rem; font: 16px/1.6 system-ui, sans-serif; color: var(--fg); background: var(--bg); }
+a { color: var(--accent); }
+nav, footer { color: var(--muted); font-size: 0.9rem; }
+nav a, footer a { margin-right: 0.75rem; }
+h1, h2, h3 { line-height: 1.25; }
+h2.part { border-bottom: 2px solid var(--accent); padding-bottom: 0.25rem; margin-top: 2.5rem; }
+pre, code { font-family: ui-monospace, Menlo, Consolas, monospace; background: var(--code-bg); border-radius: 4px; }
+pre { padding: 0.75rem 1rem; overflow-x: auto; line-height: 1.3; }
+pre.note { border-left: 3px solid var(--accent); }
+code { padding: 0 0.2rem; }
+pre code { padding: 0; }
+img { max-width: 100%; height: auto; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid var(--muted); padding: 0.25rem 0.5rem; }
+ul.quests { list-style: none; padding: 0; }
+ul.quests li { padding: 0.3rem 0; }
+.parts { color: var(--muted); font-size: 0.85rem; margin-left: 0.5rem; }
+"#;
+
+/// Directory, relative to the pages, that downloaded assets go into
+pub const ASSET_DIR: &str = "assets";
+
+/// Absolute URLs of every file a description embeds, in order, without repeats
+///
+/// `data:` URIs are already self-contained and are left out.
+pub fn asset_urls(description: &str) -> Vec<String> {
+    let document = Html::parse_fragment(description);
+    let mut urls: Vec<String> = Vec::new();
+
+    let mut add = |reference: &str| {
+        if let Some(url) = resolve(reference) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    };
+
+    for node in document.tree.root().descendants() {
+        match node.value() {
+            Node::Element(element) => {
+                for (name, value) in element.attrs() {
+                    attr_references(element.name(), name, element.attr("rel"), value)
+                        .into_iter()
+                        .for_each(&mut add);
+                }
+            }
+            Node::Text(text) if in_style(node) => css_urls(text).into_iter().for_each(&mut add),
+            _ => {}
+        }
+    }
+
+    urls
+}
+
+/// Local path (relative to the pages) for a downloaded asset
+///
+/// Names keep the original file name, prefixed with a hash of the URL so two
+/// `image.png`s from different places don't collide.
+pub fn asset_path(url: &str) -> String {
+    let name = Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments()?.next_back().map(str::to_string))
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .take(60)
+        .collect();
+    let name = if name.trim_matches('.').is_empty() { "asset".to_string() } else { name };
+
+    format!("{ASSET_DIR}/{:08x}-{name}", fnv1a(url))
+}
+
+/// One quest as listed on the index page
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    pub day: i32,
+    pub title: Option<String>,
+    pub parts: usize,
+}
+
+/// Renders the pages of one year's archive
+#[derive(Debug, Clone, Default)]
+pub struct Site {
+    pub year: i32,
+    /// Archived quests; links to these become links between pages
+    pub days: Vec<i32>,
+    /// Absolute asset URL to local path, for the assets that were downloaded
+    pub assets: HashMap<String, String>,
+    /// Shown in page footers, e.g. the date the archive was made
+    pub archived_on: String,
+}
+
+impl Site {
+    /// File name of a quest's page
+    pub fn page_name(day: i32) -> String {
+        format!("q{day:02}.html")
+    }
+
+    /// The standalone page of one quest
+    pub fn quest_page(&self, day: i32, description: &str) -> String {
+        let title = display::extract_title(description).unwrap_or_else(|| format!("Quest {day}"));
+        let parts = display::split_parts(description);
+
+        let mut body = String::new();
+        for (part, html) in (1..).zip(&parts) {
+            body.push_str(&format!("<section id=\"part-{part}\">\n"));
+            if parts.len() > 1 {
+                body.push_str(&format!("<h2 class=\"part\">Part {part}</h2>\n"));
+            }
+            body.push_str(&self.rewrite(html));
+            body.push_str("\n</section>\n");
+        }
+
+        let position = self.days.iter().position(|&d| d == day);
+        let neighbour = |offset: isize| {
+            position
+                .and_then(|i| i.checked_add_signed(offset))
+                .and_then(|i| self.days.get(i))
+        };
+        let mut nav = format!("<a href=\"index.html\">{} quests</a>", self.year);
+        if let Some(&previous) = neighbour(-1) {
+            nav.push_str(&format!("<a href=\"{}\">&larr; Quest {previous}</a>", Self::page_name(previous)));
+        }
+        if let Some(&next) = neighbour(1) {
+            nav.push_str(&format!("<a href=\"{}\">Quest {next} &rarr;</a>", Self::page_name(next)));
+        }
+
+        let source = format!("{BASE_URL}/event/{}/quests/{day}", self.year);
+        self.page(&title, &nav, &body, &source)
+    }
+
+    /// The index page listing every archived quest
+    pub fn index_page(&self, quests: &[IndexEntry]) -> String {
+        let mut body = format!("<h1>Everybody Codes {}</h1>\n<ul class=\"quests\">\n", self.year);
+        for quest in quests {
+            let title = quest.title.clone().unwrap_or_else(|| format!("Quest {}", quest.day));
+            let parts = if quest.parts == 1 { "1 part".to_string() } else { format!("{} parts", quest.parts) };
+            body.push_str(&format!(
+                "<li><a href=\"{}\">{}</a><span class=\"parts\">{parts}</span></li>\n",
+                Self::page_name(quest.day),
+                escape(&title)
+            ));
+        }
+        body.push_str("</ul>\n");
+
+        let source = format!("{BASE_URL}/event/{}", self.year);
+        self.page(&format!("Everybody Codes {}", self.year), "", &body, &source)
+    }
+
+    fn page(&self, title: &str, nav: &str, body: &str, source: &str) -> String {
+        let archived = if self.archived_on.is_empty() {
+            String::new()
+        } else {
+            format!(" on {}", escape(&self.archived_on))
+        };
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title}</title>\n<style>{STYLESHEET}</style>\n</head>\n<body>\n\
+             <nav>{nav}</nav>\n<main>\n{body}</main>\n\
+             <footer>Archived from <a href=\"{source}\">{source}</a>{archived}</footer>\n\
+             </body>\n</html>\n",
+            title = escape(title),
+        )
+    }
+
+    /// Re-serialize a description fragment with references pointing offline
+    fn rewrite(&self, html: &str) -> String {
+        let document = Html::parse_fragment(html);
+        let mut out = String::new();
+        for child in document.tree.root().children() {
+            self.serialize(child, &mut out);
+        }
+        out.trim().to_string()
+    }
+
+    fn serialize(&self, node: NodeRef<Node>, out: &mut String) {
+        match node.value() {
+            // Style contents are raw text, so escaping would corrupt the CSS
+            Node::Text(text) if in_style(node) => out.push_str(&self.rewrite_css(text)),
+            Node::Text(text) => out.push_str(&escape(text)),
+            Node::Element(element) => {
+                let name = element.name();
+                match name {
+                    // The fragment parser's wrapper
+                    "html" => {
+                        for child in node.children() {
+                            self.serialize(child, out);
+                        }
+                        return;
+                    }
+                    // Nothing in an archive should run or load remotely
+                    "script" | "iframe" => return,
+                    _ => {}
+                }
+
+                out.push('<');
+                out.push_str(name);
+                for (attr, value) in element.attrs() {
+                    if attr.starts_with("on") {
+                        continue;
+                    }
+                    let value = self.rewrite_attr(name, attr, element.attr("rel"), value);
+                    out.push_str(&format!(" {attr}=\"{}\"", escape(&value)));
+                }
+                out.push('>');
+
+                if is_void(name) {
+                    return;
+                }
+                for child in node.children() {
+                    self.serialize(child, out);
+                }
+                out.push_str(&format!("</{name}>"));
+            }
+            _ => {}
+        }
+    }
+
+    fn rewrite_attr(&self, element: &str, attr: &str, rel: Option<&str>, value: &str) -> String {
+        if is_asset_attr(element, attr, rel) {
+            return self.local(value);
+        }
+        if attr == "srcset" {
+            return value
+                .split(',')
+                .map(|candidate| match candidate.trim().split_once(char::is_whitespace) {
+                    Some((url, descriptor)) => format!("{} {}", self.local(url), descriptor.trim()),
+                    None => self.local(candidate.trim()),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+        if attr == "style" {
+            return self.rewrite_css(value);
+        }
+        if attr == "href" && !value.starts_with('#') {
+            let Some(url) = resolve(value) else {
+                return value.to_string();
+            };
+            return self.quest_link(&url).unwrap_or(url);
+        }
+        value.to_string()
+    }
+
+    /// Local copy of an asset if it was downloaded, else its absolute URL
+    fn local(&self, reference: &str) -> String {
+        resolve(reference)
+            .map(|url| self.assets.get(&url).cloned().unwrap_or(url))
+            .unwrap_or_else(|| reference.to_string())
+    }
+
+    /// Point every `url(...)` in a piece of CSS at the local copy
+    fn rewrite_css(&self, css: &str) -> String {
+        css_url_regex()
+            .replace_all(css, |caps: &Captures| match css_url(caps) {
+                Some(url) if resolve(url).is_some() => format!("url(\"{}\")", self.local(url)),
+                _ => caps[0].to_string(),
+            })
+            .into_owned()
+    }
+
+    /// Local page for a link to an archived quest of this year
+    fn quest_link(&self, url: &str) -> Option<String> {
+        let re = Regex::new(r"^https://everybody\.codes/event/(\d{4})/quests/(\d+)/?(#.*)?$").unwrap();
+        let caps = re.captures(url)?;
+        let year: i32 = caps[1].parse().ok()?;
+        let day: i32 = caps[2].parse().ok()?;
+        (year == self.year && self.days.contains(&day)).then(|| {
+            Self::page_name(day) + caps.get(3).map_or("", |m| m.as_str())
+        })
+    }
+}
+
+/// Whether an attribute loads a file that the page needs
+fn is_asset_attr(element: &str, attr: &str, rel: Option<&str>) -> bool {
+    match (element, attr) {
+        ("img" | "source" | "audio" | "video" | "track" | "input", "src") => true,
+        ("video", "poster") | ("object", "data") => true,
+        ("link", "href") => rel.is_some_and(|r| {
+            r.split_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("stylesheet") || r.eq_ignore_ascii_case("icon"))
+        }),
+        _ => false,
+    }
+}
+
+/// Every file reference in one attribute value
+fn attr_references<'a>(element: &str, attr: &str, rel: Option<&str>, value: &'a str) -> Vec<&'a str> {
+    if is_asset_attr(element, attr, rel) {
+        vec![value]
+    } else if attr == "srcset" {
+        // Each candidate is a URL optionally followed by a width or density
+        value.split(',').filter_map(|candidate| candidate.split_whitespace().next()).collect()
+    } else if attr == "style" {
+        css_urls(value)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether a text node is the contents of a `<style>` element
+fn in_style(node: NodeRef<Node>) -> bool {
+    node.parent()
+        .and_then(|parent| parent.value().as_element().map(|e| e.name() == "style"))
+        .unwrap_or(false)
+}
+
+fn css_url_regex() -> Regex {
+    Regex::new(r#"(?i)url\(\s*(?:"([^"]*)"|'([^']*)'|([^\s"')]+))\s*\)"#).unwrap()
+}
+
+/// The reference inside one `url(...)` match, without its quotes
+fn css_url<'a>(caps: &Captures<'a>) -> Option<&'a str> {
+    caps.get(1).or_else(|| caps.get(2)).or_else(|| caps.get(3)).map(|m| m.as_str())
+}
+
+fn css_urls(css: &str) -> Vec<&str> {
+    css_url_regex().captures_iter(css).filter_map(|caps| css_url(&caps)).collect()
+}
+
+/// Absolute http(s) URL for a reference, resolved against the site root
+fn resolve(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let url = Url::parse(BASE_URL).ok()?.join(value).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+fn is_void(element: &str) -> bool {
+    matches!(
+        element,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta" | "source"
+            | "track" | "wbr"
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 32-bit FNV-1a, a stable hash for asset file names
+fn fnv1a(text: &str) -> u32 {
+    text.bytes()
+        .fold(0x811c_9dc5, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+/// Files written by [`build`]
+#[derive(Debug, Clone)]
+pub struct Archive {
+    pub index: PathBuf,
+    pub pages: Vec<PathBuf>,
+    /// Number of assets stored next to the pages
+    pub assets: usize,
+    /// Assets that couldn't be downloaded and still point at the live site
+    pub failed_assets: Vec<String>,
+}
+
+/// Archive every stored description of a year under `out/{year}`
+///
+/// Assets already in the archive are kept, so re-runs work offline; others
+/// are only downloaded when `download` is set.
+pub async fn build(storage: &Storage, year: i32, out: &Path, download: bool) -> Result<Archive> {
+    let days: Vec<i32> = storage
+        .quests_with_descriptions()?
+        .into_iter()
+        .filter_map(|(y, day)| (y == year).then_some(day))
+        .collect();
+    if days.is_empty() {
+        return Err(EcError::MissingData {
+            what: format!("descriptions for {year} (run fetch first)"),
+            path: storage
+                .description_file(year, 1)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        });
+    }
+
+    let dir = out.join(year.to_string());
+    let descriptions = days
+        .iter()
+        .map(|&day| Ok((day, storage.load_description(year, day)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut assets = HashMap::new();
+    let mut failed_assets = Vec::new();
+    let mut urls: Vec<String> = Vec::new();
+    for (_, description) in &descriptions {
+        for url in asset_urls(description) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    for url in urls {
+        let local = asset_path(&url);
+        let target = dir.join(&local);
+        if target.exists() {
+            assets.insert(url, local);
+        } else if download {
+            match EcClient::download_asset(&url).await {
+                Ok(bytes) => {
+                    storage.save_binary(&target, &bytes)?;
+                    assets.insert(url, local);
+                }
+                Err(e) => {
+                    warn!("Keeping the remote reference to {url}: {e}");
+                    failed_assets.push(url);
+                }
+            }
+        }
+    }
+
+    let site = Site {
+        year,
+        days,
+        assets,
+        archived_on: chrono::Local::now().format("%Y-%m-%d").to_string(),
+    };
+
+    let mut pages = Vec::new();
+    let mut entries = Vec::new();
+    for (day, description) in &descriptions {
+        let path = dir.join(Site::page_name(*day));
+        storage.save_file(&path, &site.quest_page(*day, description))?;
+        pages.push(path);
+        entries.push(IndexEntry {
+            day: *day,
+            title: display::extract_title(description),
+            parts: display::split_parts(description).len(),
+        });
+    }
+    let index = dir.join("index.html");
+    storage.save_file(&index, &site.index_page(&entries))?;
+
+    Ok(Archive {
+        index,
+        pages,
+        assets: site.assets.len(),
+        failed_assets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = r##"<h2 id="title">Quest 2: <em>Runes</em></h2>
+<p><img src="/assets/2024/2/runes.png" alt="runes"> See <a href="/event/2024/quests/1">quest 1</a>,
+<a href="/event/2024/quests/9">quest 9</a> and <a href="#part-2">below</a>.</p>
+<pre class="note">a &lt; b</pre><script>alert(1)</script>
+<img src="data:image/png;base64,AAAA"><img src="https://cdn.example.com/x/runes.png">"##;
+
+    #[test]
+    fn test_asset_urls_are_absolute_and_unique() {
+        let urls = asset_urls(&format!("{HTML}{HTML}"));
+        assert_eq!(
+            urls,
+            vec![
+                "https://everybody.codes/assets/2024/2/runes.png".to_string(),
+                "https://cdn.example.com/x/runes.png".to_string(),
+            ]
+        );
+
+        let paths: Vec<String> = urls.iter().map(|u| asset_path(u)).collect();
+        assert!(paths.iter().all(|p| p.starts_with("assets/") && p.ends_with("-runes.png")));
+        assert_ne!(paths[0], paths[1]);
+    }
+
+    #[test]
+    fn test_quest_page_is_self_contained() {
+        let url = "https://everybody.codes/assets/2024/2/runes.png".to_string();
+        let site = Site {
+            year: 2024,
+            days: vec![1, 2],
+            assets: HashMap::from([(url.clone(), asset_path(&url))]),
+            archived_on: "2024-11-30".to_string(),
+        };
+        let page = site.quest_page(2, HTML);
+
+        assert!(page.contains("<title>Quest 2: Runes</title>"));
+        assert!(page.contains("<style>"));
+        assert!(page.contains(&format!("src=\"{}\"", asset_path(&url))));
+        assert!(page.contains("<a href=\"q01.html\">quest 1</a>"));
+        assert!(page.contains("<a href=\"https://everybody.codes/event/2024/quests/9\">quest 9</a>"));
+        assert!(page.contains("<a href=\"#part-2\">below</a>"));
+        assert!(page.contains("<pre class=\"note\">a &lt; b</pre>"));
+        assert!(page.contains("&larr; Quest 1"));
+        assert!(!page.contains("<script>"));
+        // Assets that weren't downloaded keep their absolute URL
+        assert!(page.contains("src=\"https://cdn.example.com/x/runes.png\""));
+    }
+
+    #[test]
+    fn test_srcset_and_css_references_are_localised() {
+        let html = r#"<style>pre > b { background: url('/assets/bg.png') }</style>
+<p style="background: url(&quot;https://cdn.example.com/tile.png&quot;)">x</p>
+<img srcset="/assets/a.png 1x, /assets/a@2x.png 2x" src="data:image/png;base64,AAAA">"#;
+        let urls = asset_urls(html);
+        assert_eq!(
+            urls,
+            vec![
+                "https://everybody.codes/assets/bg.png".to_string(),
+                "https://cdn.example.com/tile.png".to_string(),
+                "https://everybody.codes/assets/a.png".to_string(),
+                "https://everybody.codes/assets/a@2x.png".to_string(),
+            ]
+        );
+
+        let site = Site {
+            year: 2024,
+            assets: urls.iter().map(|u| (u.clone(), asset_path(u))).collect(),
+            ..Site::default()
+        };
+        let page = site.quest_page(1, html);
+
+        assert!(page.contains(&format!("<style>pre > b {{ background: url(\"{}\") }}</style>", asset_path(&urls[0]))));
+        assert!(page.contains(&format!("style=\"background: url(&quot;{}&quot;)\"", asset_path(&urls[1]))));
+        assert!(page.contains(&format!("srcset=\"{} 1x, {} 2x\"", asset_path(&urls[2]), asset_path(&urls[3]))));
+    }
+
+    #[test]
+    fn test_index_lists_quests() {
+        let site = Site {
+            year: 2024,
+            days: vec![1],
+            ..Site::default()
+        };
+        let index = site.index_page(&[IndexEntry {
+            day: 1,
+            title: Some("Quest 1: A & B".to_string()),
+            parts: 3,
+        }]);
+
+        assert!(index.contains("<a href=\"q01.html\">Quest 1: A &amp; B</a>"));
+        assert!(index.contains("3 parts"));
+    }
+
+    #[tokio::test]
+    async fn test_build_keeps_existing_assets_offline() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().join("data")));
+        let out = temp.path().join("archive");
+        assert!(matches!(
+            build(&storage, 2024, &out, false).await,
+            Err(EcError::MissingData { .. })
+        ));
+
+        storage.save_description(2024, 2, HTML).unwrap();
+        let url = "https://everybody.codes/assets/2024/2/runes.png";
+        let local = out.join("2024").join(asset_path(url));
+        storage.save_binary(&local, b"png").unwrap();
+
+        let archive = build(&storage, 2024, &out, false).await.unwrap();
+        assert_eq!(archive.pages, vec![out.join("2024/q02.html")]);
+        assert_eq!(archive.assets, 1);
+        assert!(archive.failed_assets.is_empty());
+
+        let page = std::fs::read_to_string(&archive.pages[0]).unwrap();
+        assert!(page.contains(&asset_path(url)));
+        assert!(page.contains("https://cdn.example.com/x/runes.png"));
+        assert!(archive.index.exists());
+    }
+}
@@ -0,0 +1,226 @@
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::{EcError, Result};
+use crate::solver::{SolverCommand, SolverRun, SolverStatus};
+use crate::storage::Storage;
+
+/// Summary statistics over repeated solver runs, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchStats {
+    pub runs: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    pub peak_rss_kb: Option<u64>,
+}
+
+impl BenchStats {
+    /// Compute statistics from measured durations (None if there are none)
+    pub fn from_durations(durations: &[Duration], peak_rss_kb: Option<u64>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+
+        let mut millis: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        millis.sort_by(f64::total_cmp);
+
+        let runs = millis.len();
+        let mean = millis.iter().sum::<f64>() / runs as f64;
+        let median = if runs.is_multiple_of(2) {
+            (millis[runs / 2 - 1] + millis[runs / 2]) / 2.0
+        } else {
+            millis[runs / 2]
+        };
+        // Sample standard deviation; a single run has none
+        let variance = if runs > 1 {
+            millis.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / (runs - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            runs,
+            min_ms: millis[0],
+            median_ms: median,
+            mean_ms: mean,
+            stddev_ms: variance.sqrt(),
+            peak_rss_kb,
+        })
+    }
+
+    /// Compare against an earlier result using the median
+    pub fn compare(&self, previous: &BenchStats, threshold_pct: f64) -> BenchComparison {
+        let change_pct = if previous.median_ms > 0.0 {
+            (self.median_ms - previous.median_ms) / previous.median_ms * 100.0
+        } else {
+            0.0
+        };
+
+        BenchComparison {
+            previous_median_ms: previous.median_ms,
+            change_pct,
+            regressed: change_pct > threshold_pct,
+        }
+    }
+}
+
+/// How a benchmark compares with the previous recorded one
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchComparison {
+    pub previous_median_ms: f64,
+    pub change_pct: f64,
+    pub regressed: bool,
+}
+
+/// A benchmark result kept in the local history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRecord {
+    pub recorded_at: String,
+    pub command: String,
+    pub stats: BenchStats,
+}
+
+/// A new benchmark and how it compares with the previous one
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub record: BenchRecord,
+    pub comparison: Option<BenchComparison>,
+}
+
+/// Benchmark the solver on a stored input and add the result to the history
+///
+/// Warmup runs aren't measured. Any failed or timed-out run aborts the
+/// benchmark, since its timings would be meaningless.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    storage: &Storage,
+    solver: &SolverCommand,
+    year: i32,
+    day: i32,
+    part: i32,
+    runs: usize,
+    warmup: usize,
+    threshold_pct: f64,
+) -> Result<BenchReport> {
+    let input = storage.input_file(year, day, part);
+    if !storage.has_input(year, day, part) {
+        return Err(EcError::MissingData {
+            what: "input".to_string(),
+            path: input,
+        });
+    }
+
+    let check = |run: SolverRun| match run.status {
+        SolverStatus::Success => Ok(run),
+        SolverStatus::TimedOut => Err(EcError::SolverTimedOut {
+            timeout: solver.limits().timeout.unwrap_or_default(),
+        }),
+        status => Err(EcError::SolverFailed {
+            reason: status.to_string(),
+        }),
+    };
+
+    for i in 0..warmup {
+        debug!("Warmup run {}/{warmup}", i + 1);
+        check(solver.run(year, day, part, &input)?)?;
+    }
+
+    let mut durations = Vec::with_capacity(runs);
+    let mut peak_rss_kb = None;
+    let mut answers = Vec::new();
+    for i in 0..runs {
+        info!("Run {}/{runs}...", i + 1);
+        let run = check(solver.run(year, day, part, &input)?)?;
+        durations.push(run.duration);
+        peak_rss_kb = peak_rss_kb.max(run.peak_rss_kb);
+        answers.push(run.answer);
+    }
+
+    answers.dedup();
+    if answers.len() > 1 {
+        warn!("Solver produced different answers across runs: {answers:?}");
+    }
+
+    let stats = BenchStats::from_durations(&durations, peak_rss_kb)
+        .ok_or_else(|| EcError::InvalidArgument("at least one run is needed".to_string()))?;
+    let history = storage.load_bench_history(year, day, part)?;
+    let comparison = history.last().map(|previous| stats.compare(&previous.stats, threshold_pct));
+
+    let record = BenchRecord {
+        recorded_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        command: solver.render(year, day, part, &input),
+        stats,
+    };
+    storage.record_bench(year, day, part, &record)?;
+
+    Ok(BenchReport { record, comparison })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    #[test]
+    fn test_stats_from_durations() {
+        let stats = BenchStats::from_durations(&ms(&[40, 10, 30, 20]), Some(1024)).unwrap();
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.median_ms, 25.0);
+        assert_eq!(stats.mean_ms, 25.0);
+        assert!((stats.stddev_ms - 12.909).abs() < 0.001);
+        assert!(BenchStats::from_durations(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_compare_flags_regression_beyond_threshold() {
+        let before = BenchStats::from_durations(&ms(&[100]), None).unwrap();
+        let slower = BenchStats::from_durations(&ms(&[120]), None).unwrap();
+
+        let comparison = slower.compare(&before, 10.0);
+        assert!(comparison.regressed);
+        assert!((comparison.change_pct - 20.0).abs() < 1e-9);
+        assert!(!slower.compare(&before, 25.0).regressed);
+    }
+
+    #[test]
+    fn test_run_records_history() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        let solver = SolverCommand::new("cat");
+        assert!(matches!(
+            run(&storage, &solver, 2024, 1, 1, 2, 0, 10.0),
+            Err(EcError::MissingData { .. })
+        ));
+
+        storage.save_input(2024, 1, 1, "42\n").unwrap();
+        let first = run(&storage, &solver, 2024, 1, 1, 2, 1, 10.0).unwrap();
+        assert_eq!(first.record.stats.runs, 2);
+        assert!(first.comparison.is_none());
+
+        let second = run(&storage, &solver, 2024, 1, 1, 1, 0, 10.0).unwrap();
+        let comparison = second.comparison.unwrap();
+        assert_eq!(comparison.previous_median_ms, first.record.stats.median_ms);
+        assert_eq!(storage.load_bench_history(2024, 1, 1).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_failed_run_aborts() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        storage.save_input(2024, 1, 1, "42\n").unwrap();
+
+        let solver = SolverCommand::new("exit 1");
+        assert!(matches!(
+            run(&storage, &solver, 2024, 1, 1, 3, 0, 10.0),
+            Err(EcError::SolverFailed { .. })
+        ));
+        assert!(storage.load_bench_history(2024, 1, 1).unwrap().is_empty());
+    }
+}
@@ -1,5 +1,9 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use chrono::{Datelike, NaiveDate, Utc, Weekday};
+use ec_cli::extract::SamplePick;
+use ec_cli::solver::SolverLimits;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Calculate the default quest year based on current date
 ///
@@ -56,6 +60,78 @@ pub struct Cli {
     /// Base directory for storing files (default: "data")
     #[arg(long, global = true)]
     pub base_path: Option<String>,
+
+    /// Show what would be written or sent without changing anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Output format for command results (logs always go to stderr)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+/// Resource limits for spawned solver processes
+#[derive(Args, Clone, Debug)]
+pub struct LimitArgs {
+    /// Kill the solver (and its process group) after this many seconds
+    #[arg(long, env = "EC_TIMEOUT")]
+    pub timeout: Option<f64>,
+
+    /// Address-space limit for the solver in MiB (Unix only)
+    #[arg(long, env = "EC_MEMORY_LIMIT")]
+    pub memory_limit: Option<u64>,
+
+    /// Kill the solver after it prints this many KiB
+    #[arg(long, env = "EC_MAX_OUTPUT")]
+    pub max_output: Option<usize>,
+}
+
+impl LimitArgs {
+    /// Reject limits that are out of range or too large to represent
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(t) = self.timeout {
+            if t.is_nan() || t <= 0.0 {
+                return Err("Invalid timeout (must be a positive number of seconds)".to_string());
+            }
+            if Duration::try_from_secs_f64(t).is_err() {
+                return Err(format!("Invalid timeout: {t} seconds is too large"));
+            }
+        }
+        if let Some(mib) = self.memory_limit.filter(|mib| mib.checked_mul(1024 * 1024).is_none()) {
+            return Err(format!("Invalid memory limit: {mib} MiB is too large"));
+        }
+        if let Some(kib) = self.max_output.filter(|kib| kib.checked_mul(1024).is_none()) {
+            return Err(format!("Invalid output limit: {kib} KiB is too large"));
+        }
+        Ok(())
+    }
+
+    /// Limits in the units the solver uses; call [`LimitArgs::validate`] first
+    pub fn limits(&self) -> SolverLimits {
+        SolverLimits {
+            timeout: self
+                .timeout
+                .map(|t| Duration::try_from_secs_f64(t).unwrap_or(Duration::MAX)),
+            memory_bytes: self.memory_limit.map(|mib| mib.saturating_mul(1024 * 1024)),
+            max_output_bytes: self.max_output.map(|kib| kib.saturating_mul(1024)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// A single JSON document on stdout
+    Json,
+}
+
+/// Document formats for `export`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// CommonMark with YAML front-matter
+    #[value(alias = "markdown")]
+    Md,
 }
 
 #[derive(Subcommand)]
@@ -112,6 +188,88 @@ pub enum Commands {
         /// Terminal width for text wrapping
         #[arg(short, long)]
         width: Option<usize>,
+
+        /// Only show this part (1-3)
+        #[arg(short, long)]
+        part: Option<i32>,
+
+        /// Only show the newest unlocked part
+        #[arg(long, conflicts_with = "part")]
+        latest: bool,
+
+        /// Only show what changed since the previous stored version
+        #[arg(long, conflicts_with_all = ["part", "latest"])]
+        new: bool,
+
+        /// Plain html2text output, without styling
+        #[arg(long)]
+        plain: bool,
+
+        /// Print directly instead of paging long descriptions
+        #[arg(long)]
+        no_pager: bool,
+    },
+
+    /// Convert stored descriptions into Markdown notes
+    Export {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20); every stored description of the year when omitted
+        #[arg(short, long)]
+        day: Option<i32>,
+
+        /// Document format. Named `--to` rather than `--format` because the
+        /// global `--format` already selects text or JSON output
+        #[arg(long, value_enum, default_value_t = ExportFormat::Md)]
+        to: ExportFormat,
+
+        /// Directory to write `{year}/q{day}.md` files to; required for a whole
+        /// year, a single day is printed when omitted
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Build a self-contained HTML site of a year's stored descriptions
+    Archive {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Directory to build the site in (pages go in `{out}/{year}/`)
+        #[arg(short, long, default_value = "archive")]
+        out: PathBuf,
+
+        /// Keep images and other assets pointing at the live site
+        #[arg(long)]
+        skip_assets: bool,
+    },
+
+    /// Search the text of every stored description
+    Search {
+        /// Regular expression to look for (case-insensitive unless --case-sensitive)
+        query: String,
+
+        /// Only search this year
+        #[arg(short, long)]
+        year: Option<i32>,
+
+        /// Match the query literally instead of as a regex
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+
+        /// Match case exactly
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Show at most this many results
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Read the best match, at its part
+        #[arg(long)]
+        open: bool,
     },
 
     /// Submit puzzle answer
@@ -128,28 +286,316 @@ pub enum Commands {
         #[arg(short, long)]
         part: i32,
 
-        /// Answer to submit
-        answer: String,
+        /// Answer to submit ("-" reads it from stdin)
+        #[arg(required_unless_present_any = ["from_file", "from_command"])]
+        answer: Option<String>,
+
+        /// Read the answer from a file
+        #[arg(long, conflicts_with_all = ["answer", "from_command"])]
+        from_file: Option<String>,
+
+        /// Run a shell command and submit its output
+        #[arg(long, conflicts_with = "answer")]
+        from_command: Option<String>,
+
+        /// Submit without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+
+        /// After a correct answer, fetch and display the newly unlocked part
+        #[arg(long)]
+        advance: bool,
+    },
+
+    /// Choose which blocks of a part are its samples and expected answers
+    Samples {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long)]
+        day: i32,
+
+        /// Quest part (1-3)
+        #[arg(short, long)]
+        part: i32,
+
+        /// A sample as block numbers, e.g. `2:3` or `1+2:4` (repeatable)
+        #[arg(long = "pick", value_name = "INPUT[+INPUT][:ANSWER]")]
+        picks: Vec<SamplePick>,
+
+        /// Only list the blocks, without prompting
+        #[arg(long, conflicts_with = "picks")]
+        list: bool,
+
+        /// Forget the picks and go back to automatic extraction
+        #[arg(long, conflicts_with_all = ["picks", "list"])]
+        reset: bool,
+    },
+
+    /// Summarize everything known locally about a quest
+    Status {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long)]
+        day: i32,
+    },
+
+    /// Generate a solution skeleton for a quest from a template
+    New {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long)]
+        day: i32,
+
+        /// Template language (built-in: rust, python)
+        #[arg(short, long, default_value = "rust")]
+        lang: String,
+
+        /// Directory of user templates, one subdirectory per language
+        #[arg(long, env = "EC_TEMPLATE_DIR")]
+        template_dir: Option<PathBuf>,
+
+        /// Add the quest as a member of the enclosing Cargo workspace
+        #[arg(long, conflicts_with = "lang")]
+        cargo: bool,
+
+        /// Directory to generate files in (with --cargo, where to look for the workspace)
+        #[arg(short, long, default_value = ".")]
+        out: PathBuf,
+    },
+
+    /// Fetch and display the earliest unsolved part of the event
+    Next {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Terminal width for text wrapping
+        #[arg(short, long)]
+        width: Option<usize>,
+    },
+
+    /// Run registered Rust solutions (via `cargo run`) on samples and inputs
+    Run {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long, required_unless_present = "all")]
+        day: Option<i32>,
+
+        /// Quest part (1-3)
+        #[arg(short, long, requires = "day")]
+        part: Option<i32>,
+
+        /// Only run the Nth sample of each part
+        #[arg(short, long, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Run every registered quest, of every year
+        #[arg(long, conflicts_with_all = ["year", "day"])]
+        all: bool,
+
+        /// Binary target of the solutions crate
+        #[arg(long)]
+        bin: Option<String>,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Benchmark a solver on a stored input
+    Bench {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long)]
+        day: i32,
+
+        /// Quest part (1-3)
+        #[arg(short, long)]
+        part: i32,
+
+        /// Number of measured runs
+        #[arg(short = 'n', long, default_value_t = 10)]
+        runs: usize,
+
+        /// Number of unmeasured warmup runs
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+
+        /// Median slowdown (percent) flagged as a regression
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+
+        /// Solver command (defaults to $EC_SOLVER)
+        #[arg(last = true)]
+        command: Vec<String>,
+
+        #[command(flatten)]
+        limits: LimitArgs,
     },
+
+    /// Re-run a solver on the sample and input whenever sources change
+    Watch {
+        /// Quest year
+        #[arg(short, long, default_value_t = default_year().parse().unwrap())]
+        year: i32,
+
+        /// Quest day (1-20)
+        #[arg(short, long)]
+        day: i32,
+
+        /// Quest part (1-3)
+        #[arg(short, long)]
+        part: i32,
+
+        /// Only run the Nth sample
+        #[arg(short, long, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Glob of files to watch (repeatable)
+        #[arg(long = "watch", value_name = "GLOB", default_value = "src/**/*")]
+        patterns: Vec<String>,
+
+        /// Quiet period after a change before re-running, in milliseconds
+        #[arg(long, default_value_t = 200)]
+        debounce: u64,
+
+        /// Solver command (defaults to $EC_SOLVER)
+        #[arg(last = true)]
+        command: Vec<String>,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Re-run the solver on every part with an accepted answer
+    Regress {
+        /// Only check this year
+        #[arg(short, long)]
+        year: Option<i32>,
+
+        /// Solver command (defaults to $EC_SOLVER)
+        #[arg(last = true)]
+        command: Vec<String>,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+    },
+}
+
+impl Commands {
+    /// Subcommand name as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Fetch { .. } => "fetch",
+            Commands::Read { .. } => "read",
+            Commands::Export { .. } => "export",
+            Commands::Archive { .. } => "archive",
+            Commands::Search { .. } => "search",
+            Commands::Submit { .. } => "submit",
+            Commands::Status { .. } => "status",
+            Commands::Samples { .. } => "samples",
+            Commands::New { .. } => "new",
+            Commands::Next { .. } => "next",
+            Commands::Run { .. } => "run",
+            Commands::Regress { .. } => "regress",
+            Commands::Bench { .. } => "bench",
+            Commands::Watch { .. } => "watch",
+        }
+    }
 }
 
 impl Cli {
     pub fn validate(&self) -> Result<(), String> {
+        if let Commands::Run { limits, .. }
+        | Commands::Submit { limits, .. }
+        | Commands::Bench { limits, .. }
+        | Commands::Watch { limits, .. }
+        | Commands::Regress { limits, .. } = &self.command
+        {
+            limits.validate()?;
+        }
+
+        if let Commands::Run { sample: Some(0), .. } | Commands::Watch { sample: Some(0), .. } =
+            &self.command
+        {
+            return Err("Invalid sample: 0 (samples are numbered from 1)".to_string());
+        }
+
         match &self.command {
             Commands::Fetch { year, day, part, .. } => {
                 validate_year(*year)?;
                 validate_day(*day)?;
                 validate_part(*part)?;
             }
-            Commands::Read { year, day, .. } => {
+            Commands::Read { year, day, part, .. } => {
                 validate_year(*year)?;
                 validate_day(*day)?;
+                if let Some(part) = part {
+                    validate_part(*part)?;
+                }
             }
             Commands::Submit { year, day, part, .. } => {
                 validate_year(*year)?;
                 validate_day(*day)?;
                 validate_part(*part)?;
             }
+            Commands::Status { year, day } | Commands::New { year, day, .. } => {
+                validate_year(*year)?;
+                validate_day(*day)?;
+            }
+            Commands::Next { year, .. } | Commands::Archive { year, .. } => {
+                validate_year(*year)?;
+            }
+            Commands::Watch { year, day, part, .. } | Commands::Samples { year, day, part, .. } => {
+                validate_year(*year)?;
+                validate_day(*day)?;
+                validate_part(*part)?;
+            }
+            Commands::Bench { year, day, part, runs, .. } => {
+                validate_year(*year)?;
+                validate_day(*day)?;
+                validate_part(*part)?;
+                if *runs == 0 {
+                    return Err("Invalid runs: 0 (must be at least 1)".to_string());
+                }
+            }
+            Commands::Export { year, day, .. } => {
+                validate_year(*year)?;
+                if let Some(day) = day {
+                    validate_day(*day)?;
+                }
+            }
+            Commands::Regress { year, .. } | Commands::Search { year, .. } => {
+                if let Some(year) = year {
+                    validate_year(*year)?;
+                }
+            }
+            Commands::Run { year, day, part, .. } => {
+                validate_year(*year)?;
+                if let Some(day) = day {
+                    validate_day(*day)?;
+                }
+                if let Some(part) = part {
+                    validate_part(*part)?;
+                }
+            }
         }
         Ok(())
     }
@@ -175,3 +621,23 @@ fn validate_part(part: i32) -> Result<(), String> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(args: &[&str]) -> Result<(), String> {
+        Cli::try_parse_from(["ec-cli"].iter().chain(args)).unwrap().validate()
+    }
+
+    #[test]
+    fn test_limits_must_be_representable() {
+        assert!(validate(&["bench", "-d", "1", "-p", "1", "--timeout", "2.5"]).is_ok());
+        for timeout in ["0", "-1", "NaN", "inf", "1e20"] {
+            let arg = format!("--timeout={timeout}");
+            assert!(validate(&["bench", "-d", "1", "-p", "1", &arg]).is_err(), "{timeout}");
+        }
+        assert!(validate(&["regress", "--memory-limit", "18446744073709551615"]).is_err());
+        assert!(validate(&["regress", "--max-output", &usize::MAX.to_string()]).is_err());
+    }
+}
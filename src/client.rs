@@ -5,12 +5,14 @@ use std::fs;
 
 use crate::crypto::decrypt_aes_cbc;
 use crate::error::{EcError, Result};
-use crate::models::{AnswerPayload, QuestKeys, SubmitResponse, User};
+use crate::models::{AnswerPayload, QuestKeys, RequestPreview, SubmitResponse, User};
 
-const BASE_URL: &str = "https://everybody.codes";
+/// Site root, also used to resolve links in descriptions
+pub const BASE_URL: &str = "https://everybody.codes";
 const CDN_URL: &str = "https://everybody-codes.b-cdn.net";
 const USER_AGENT: &str = "ec-cli/0.1.0";
 
+/// Authenticated client for the Everybody Codes API and CDN
 pub struct EcClient {
     client: Client,
     cookie: String,
@@ -73,6 +75,15 @@ impl EcClient {
         Err(EcError::MissingCookie)
     }
 
+    /// Parse the Retry-After header (in seconds) of a response, if present
+    fn retry_after(response: &reqwest::Response) -> Option<u64> {
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+    }
+
     /// Fetch user seed from API (cached after first call)
     pub async fn get_user_seed(&mut self) -> Result<i32> {
         if let Some(seed) = self.user_seed {
@@ -88,11 +99,13 @@ impl EcClient {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            return Err(EcError::HttpError {
-                status: response.status().as_u16(),
-                message: format!("Failed to fetch user seed: {}", response.status()),
-            });
+        let status = response.status();
+        if !status.is_success() {
+            return Err(EcError::from_status(
+                status,
+                Self::retry_after(&response),
+                format!("Failed to fetch user seed: {status}"),
+            ));
         }
 
         let body = response.text().await?;
@@ -120,16 +133,13 @@ impl EcClient {
         if !status.is_success() {
             // 404 typically means the quest day isn't available yet
             if status == reqwest::StatusCode::NOT_FOUND {
-                return Err(EcError::QuestNotAvailable {
-                    year,
-                    day,
-                    part: 1 // Default to part 1 for day-level errors
-                });
+                return Err(EcError::QuestNotAvailable { year, day, part: None });
             }
-            return Err(EcError::HttpError {
-                status: status.as_u16(),
-                message: format!("Failed to fetch quest keys: {status}"),
-            });
+            return Err(EcError::from_status(
+                status,
+                Self::retry_after(&response),
+                format!("Failed to fetch quest keys: {status}"),
+            ));
         }
 
         // Get response text first for better error messages
@@ -138,22 +148,14 @@ impl EcClient {
 
         // Check if it's an empty object (quest not available yet)
         if body.trim() == "{}" {
-            return Err(EcError::QuestNotAvailable {
-                year,
-                day,
-                part: 1
-            });
+            return Err(EcError::QuestNotAvailable { year, day, part: None });
         }
 
         let keys: QuestKeys = serde_json::from_str(&body)
             .map_err(|e| {
                 // If JSON parsing fails and key1 is missing, quest likely not available
                 if e.to_string().contains("missing field `key1`") {
-                    EcError::QuestNotAvailable {
-                        year,
-                        day,
-                        part: 1
-                    }
+                    EcError::QuestNotAvailable { year, day, part: None }
                 } else {
                     EcError::JsonError(e)
                 }
@@ -168,7 +170,7 @@ impl EcClient {
         let seed = self.get_user_seed().await?;
         let keys = self.fetch_quest_keys(year, day).await?;
         let key = keys.get_key(part)
-            .map_err(|_| EcError::QuestNotAvailable { year, day, part })?;
+            .map_err(|_| EcError::QuestNotAvailable { year, day, part: Some(part) })?;
 
         info!("Downloading encrypted input for {year}/{day} part {part}...");
         let url = format!("{CDN_URL}/assets/{year}/{day}/input/{seed}.json");
@@ -182,12 +184,13 @@ impl EcClient {
         let status = response.status();
         if !status.is_success() {
             if status == reqwest::StatusCode::NOT_FOUND {
-                return Err(EcError::QuestNotAvailable { year, day, part });
+                return Err(EcError::QuestNotAvailable { year, day, part: Some(part) });
             }
-            return Err(EcError::HttpError {
-                status: status.as_u16(),
-                message: format!("Failed to fetch input: {status}"),
-            });
+            return Err(EcError::from_status(
+                status,
+                Self::retry_after(&response),
+                format!("Failed to fetch input: {status}"),
+            ));
         }
 
         let body = response.text().await?;
@@ -228,16 +231,14 @@ impl EcClient {
         let status = response.status();
         if !status.is_success() {
             if status == reqwest::StatusCode::NOT_FOUND {
-                return Err(EcError::QuestNotAvailable {
-                    year,
-                    day,
-                    part: 1 // Description is day-level
-                });
+                // Description is day-level
+                return Err(EcError::QuestNotAvailable { year, day, part: None });
             }
-            return Err(EcError::HttpError {
-                status: status.as_u16(),
-                message: format!("Failed to fetch description: {status}"),
-            });
+            return Err(EcError::from_status(
+                status,
+                Self::retry_after(&response),
+                format!("Failed to fetch description: {status}"),
+            ));
         }
 
         let body = response.text().await?;
@@ -276,6 +277,56 @@ impl EcClient {
         Ok(combined)
     }
 
+    /// Download a file referenced by a description, such as an image
+    ///
+    /// Assets are public, so no cookie is sent and none is needed.
+    #[cfg(feature = "cli")]
+    pub async fn download_asset(url: &str) -> Result<Vec<u8>> {
+        debug!("Downloading asset {url}");
+        let client = Client::builder().user_agent(USER_AGENT).build()?;
+        let response = client.get(url).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(EcError::from_status(
+                status,
+                Self::retry_after(&response),
+                format!("Failed to download {url}: {status}"),
+            ));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// URL that answers for a part are posted to
+    fn answer_url(year: i32, day: i32, part: i32) -> String {
+        format!("{BASE_URL}/api/event/{year}/quest/{day}/part/{part}/answer")
+    }
+
+    /// Describe the request `submit_answer` would send, with the cookie redacted
+    pub fn submit_request_preview(
+        &self,
+        year: i32,
+        day: i32,
+        part: i32,
+        answer: &str,
+    ) -> RequestPreview {
+        let payload = AnswerPayload {
+            answer: answer.to_string(),
+        };
+
+        RequestPreview {
+            method: "POST".to_string(),
+            url: Self::answer_url(year, day, part),
+            headers: vec![
+                ("User-Agent".to_string(), USER_AGENT.to_string()),
+                ("Cookie".to_string(), "everybody-codes=<redacted>".to_string()),
+                ("Content-Type".to_string(), "application/json".to_string()),
+            ],
+            body: serde_json::to_string(&payload).unwrap_or_default(),
+        }
+    }
+
     /// Submit an answer for a puzzle
     pub async fn submit_answer(
         &self,
@@ -285,9 +336,7 @@ impl EcClient {
         answer: &str,
     ) -> Result<SubmitResponse> {
         info!("Submitting answer for {year}/{day} part {part}...");
-        let url = format!(
-            "{BASE_URL}/api/event/{year}/quest/{day}/part/{part}/answer"
-        );
+        let url = Self::answer_url(year, day, part);
 
         let payload = AnswerPayload {
             answer: answer.to_string(),
@@ -302,13 +351,17 @@ impl EcClient {
 
         match response.status() {
             StatusCode::CONFLICT => {
-                return Err(EcError::AlreadySubmitted);
+                return Err(EcError::AlreadySubmitted { year, day, part });
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(EcError::QuestNotAvailable { year, day, part: Some(part) });
             }
             status if !status.is_success() => {
-                return Err(EcError::HttpError {
-                    status: status.as_u16(),
-                    message: format!("Failed to submit answer: {status}"),
-                });
+                return Err(EcError::from_status(
+                    status,
+                    Self::retry_after(&response),
+                    format!("Failed to submit answer: {status}"),
+                ));
             }
             _ => {}
         }
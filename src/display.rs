@@ -1,28 +1,100 @@
 use html2text::from_read;
 use regex::Regex;
 
+use crate::extract;
+
 /// Extract sample/example data from HTML description
 ///
-/// Looks for content within <pre class="note"> tags
+/// Returns the text of every `<pre class="note">` block; see
+/// [`crate::extract`] for samples paired with their answers
 pub fn extract_samples(html: &str) -> Vec<String> {
-    let re = Regex::new(r#"(?s)<pre class="note">(.*?)</pre>"#).unwrap();
-
-    re.captures_iter(html)
-        .map(|cap| cap[1].trim_start().to_string())
-        .collect()
+    extract::sample_inputs(html)
 }
 
 /// Extract expected answer from HTML description
 ///
-/// Looks for the last occurrence of <pre> <b>ANSWER</b> </pre> pattern
-/// which typically appears at the end of example sections
+/// Returns the last bold-only answer block, which typically appears at the
+/// end of example sections
 pub fn extract_expected_answer(html: &str) -> Option<String> {
-    let re = Regex::new(r#"<pre>\s*<b>([^<]+)</b>\s*</pre>"#).unwrap();
+    extract::answers(html).pop()
+}
+
+/// Split a combined description into per-part HTML sections
+///
+/// `EcClient::fetch_description` joins parts with a "PART N" banner framed
+/// by "=" lines; element 0 is part 1, element 1 part 2, and so on
+pub fn split_parts(description: &str) -> Vec<&str> {
+    let re = Regex::new(r"\n*={80}\n PART \d \n={80}\n*").unwrap();
+
+    re.split(description).collect()
+}
+
+/// Parts and blocks of `current` that `previous` doesn't have
+///
+/// A part missing from `previous` is returned whole. For a part in both, only
+/// its inserted or changed top-level blocks (paragraphs, `<pre>`, headings...)
+/// are returned, joined back into one fragment. Unchanged parts are left out.
+pub fn added_sections(previous: &str, current: &str) -> Vec<(i32, String)> {
+    use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+    let old_parts = split_parts(previous);
+
+    (1..)
+        .zip(split_parts(current))
+        .filter_map(|(part, html)| {
+            let Some(old_html) = old_parts.get(part as usize - 1) else {
+                return Some((part, html.to_string()));
+            };
+
+            let old_blocks = top_level_blocks(old_html);
+            let new_blocks = top_level_blocks(html);
+            let added: Vec<String> = capture_diff_slices(Algorithm::Myers, &old_blocks, &new_blocks)
+                .into_iter()
+                .filter_map(|op| match op {
+                    DiffOp::Insert { new_index, new_len, .. }
+                    | DiffOp::Replace { new_index, new_len, .. } => {
+                        Some(new_blocks[new_index..new_index + new_len].join("\n"))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            (!added.is_empty()).then(|| (part, added.join("\n")))
+        })
+        .collect()
+}
+
+/// The HTML of each top-level element of a fragment, skipping blank text
+fn top_level_blocks(html: &str) -> Vec<String> {
+    use scraper::{ElementRef, Html, Node};
+
+    let document = Html::parse_fragment(html);
+    document
+        .root_element()
+        .children()
+        .filter_map(|child| match child.value() {
+            Node::Element(_) => ElementRef::wrap(child).map(|e| e.html()),
+            Node::Text(text) if !text.trim().is_empty() => Some(
+                text.trim()
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;"),
+            ),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Extract the quest title from HTML description
+///
+/// Uses the text of the first heading (<h1>-<h3>), with nested tags stripped
+pub fn extract_title(html: &str) -> Option<String> {
+    let re = Regex::new(r#"(?s)<h[1-3][^>]*>(.*?)</h[1-3]>"#).unwrap();
+    let tags = Regex::new(r#"<[^>]+>"#).unwrap();
 
-    // Get the last match, as that's typically the final answer
-    re.captures_iter(html)
-        .last()
-        .map(|cap| cap[1].trim().to_string())
+    re.captures(html)
+        .map(|cap| tags.replace_all(&cap[1], "").trim().to_string())
+        .filter(|title| !title.is_empty())
 }
 
 /// Convert HTML to plain text for terminal display
@@ -61,6 +133,295 @@ pub fn format_submit_response(response: &crate::models::SubmitResponse) -> Strin
     output
 }
 
+/// Format local quest status for display
+pub fn format_status(status: &crate::models::QuestStatus) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("Quest {}/{}", status.year, status.day));
+    if let Some(title) = &status.title {
+        output.push_str(&format!(": {title}"));
+    }
+    output.push('\n');
+
+    match &status.description_path {
+        Some(path) => output.push_str(&format!("  Description: {}\n", path.display())),
+        None => output.push_str("  Description: not fetched\n"),
+    }
+
+    for part in &status.parts {
+        let state = match part.unlocked {
+            _ if part.is_solved() => "solved",
+            Some(true) => "unlocked",
+            Some(false) => "locked",
+            None => "unknown (keys not cached)",
+        };
+        output.push_str(&format!("\nPart {} [{state}]\n", part.part));
+
+        let path_or_missing = |path: &Option<std::path::PathBuf>| {
+            path.as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "missing".to_string())
+        };
+        output.push_str(&format!("  Input:           {}\n", path_or_missing(&part.input_path)));
+        if part.samples.is_empty() {
+            output.push_str("  Samples:         missing\n");
+        } else {
+            output.push_str("  Samples:\n");
+            for sample in &part.samples {
+                let answer = sample
+                    .expected_answer
+                    .as_deref()
+                    .map(|a| format!("→ {a}"))
+                    .unwrap_or_else(|| "(no expected answer paired)".to_string());
+                output.push_str(&format!("    {}. {}  {answer}\n", sample.number, sample.path.display()));
+            }
+        }
+
+        if part.submissions.is_empty() {
+            output.push_str("  Submissions:     none\n");
+        } else {
+            output.push_str("  Submissions:\n");
+            for submission in &part.submissions {
+                let outcome = if submission.correct {
+                    "✓ correct".to_string()
+                } else if submission.length_correct {
+                    "✗ incorrect (length correct)".to_string()
+                } else {
+                    "✗ incorrect".to_string()
+                };
+                output.push_str(&format!(
+                    "    {}  {:<20} {outcome}\n",
+                    submission.submitted_at, submission.answer
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+/// Format solution harness results as a table
+pub fn format_run_reports(reports: &[crate::harness::RunReport]) -> String {
+    use crate::harness::{RunKind, Verdict};
+
+    let mut output = String::new();
+
+    for report in reports {
+        let kind = match (report.kind, report.sample) {
+            (RunKind::Sample, Some(n)) => format!("sample {n}"),
+            (RunKind::Sample, None) => "sample".to_string(),
+            (RunKind::Input, _) => "input".to_string(),
+        };
+        let verdict = match report.verdict {
+            Verdict::Pass => "✓ pass",
+            Verdict::Fail => "✗ FAIL",
+            Verdict::Unverified => "? unverified",
+            Verdict::Skipped => "- skipped",
+        };
+        output.push_str(&format!(
+            "{}/{:<2} part {} {:<9}  {:<12}  {:>10.3}ms  {}",
+            report.year,
+            report.day,
+            report.part,
+            kind,
+            verdict,
+            report.duration.as_secs_f64() * 1000.0,
+            report.answer.as_deref().unwrap_or("-"),
+        ));
+        if report.verdict == Verdict::Fail {
+            if let Some(expected) = &report.expected {
+                output.push_str(&format!(" (expected {expected})"));
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Format a part's `<pre>` blocks for the sample picker
+///
+/// Long blocks are cut after a few lines; the guess column shows what
+/// automatic extraction made of each block
+pub fn format_pre_blocks(blocks: &[crate::extract::PreBlock]) -> String {
+    use crate::extract::BlockGuess;
+    const MAX_LINES: usize = 6;
+
+    let mut output = String::new();
+
+    for block in blocks {
+        let guess = match block.guess {
+            Some(BlockGuess::Sample) => "sample?",
+            Some(BlockGuess::Answer) => "answer?",
+            None => "",
+        };
+        output.push_str(&format!("[{}] {guess}\n", block.number));
+        if let Some(context) = &block.context_text {
+            output.push_str(&format!("    after: \"{context}\"\n"));
+        }
+
+        let lines: Vec<&str> = block.text.lines().collect();
+        for line in lines.iter().take(MAX_LINES) {
+            output.push_str(&format!("    │ {line}\n"));
+        }
+        if lines.len() > MAX_LINES {
+            output.push_str(&format!("    │ … {} more line(s)\n", lines.len() - MAX_LINES));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Format ranked search hits, one header line and one snippet line each
+///
+/// With `color`, matches in the snippet are shown in bold yellow
+pub fn format_search_hits(hits: &[crate::search::SearchHit], color: bool) -> String {
+    let mut output = String::new();
+
+    for hit in hits {
+        output.push_str(&format!("{}/{} part {}", hit.year, hit.day, hit.part));
+        if let Some(title) = &hit.title {
+            output.push_str(&format!("  {title}"));
+        }
+        let plural = if hit.matches == 1 { "" } else { "es" };
+        output.push_str(&format!("  ({} match{plural})\n", hit.matches));
+
+        let mut snippet = String::new();
+        let mut last = 0;
+        for &(start, end) in &hit.highlights {
+            snippet.push_str(&hit.snippet[last..start]);
+            if color {
+                snippet.push_str(&format!("\x1b[1;33m{}\x1b[0m", &hit.snippet[start..end]));
+            } else {
+                snippet.push_str(&hit.snippet[start..end]);
+            }
+            last = end;
+        }
+        snippet.push_str(&hit.snippet[last..]);
+        output.push_str(&format!("    {snippet}\n"));
+    }
+
+    output
+}
+
+/// Format regression results as a quest × part matrix, then list failures
+pub fn format_regress_matrix(results: &[crate::models::RegressResult]) -> String {
+    use crate::models::RegressOutcome;
+
+    let mut output = format!("{:<10}{:<18}{:<18}{:<18}\n", "Quest", "Part 1", "Part 2", "Part 3");
+
+    let mut quests: Vec<(i32, i32)> = results.iter().map(|r| (r.year, r.day)).collect();
+    quests.dedup();
+
+    for (year, day) in quests {
+        output.push_str(&format!("{:<10}", format!("{year}/{day}")));
+        for part in 1..=3 {
+            let cell = results
+                .iter()
+                .find(|r| r.year == year && r.day == day && r.part == part)
+                .map(|r| {
+                    let mark = match r.outcome {
+                        RegressOutcome::Pass => "✓",
+                        RegressOutcome::Fail => "✗",
+                        RegressOutcome::Error => "!",
+                        RegressOutcome::Timeout => "⏱",
+                        RegressOutcome::MissingInput => "?",
+                    };
+                    match &r.run {
+                        Some(run) => format!("{mark} {:>9.1}ms", run.duration.as_secs_f64() * 1000.0),
+                        None => mark.to_string(),
+                    }
+                })
+                .unwrap_or_else(|| "·".to_string());
+            output.push_str(&format!("{cell:<18}"));
+        }
+        output.push('\n');
+    }
+
+    for result in results.iter().filter(|r| r.outcome != RegressOutcome::Pass) {
+        let detail = match result.outcome {
+            RegressOutcome::Fail => format!(
+                "expected {}, got {}",
+                result.expected,
+                result.run.as_ref().and_then(|r| r.answer.as_deref()).unwrap_or("nothing")
+            ),
+            RegressOutcome::Error => format!(
+                "solver {}",
+                result.run.as_ref().map(|r| r.status.to_string()).unwrap_or_default()
+            ),
+            RegressOutcome::Timeout => "solver timed out".to_string(),
+            RegressOutcome::MissingInput => "input not stored".to_string(),
+            RegressOutcome::Pass => continue,
+        };
+        output.push_str(&format!("\n{}/{} part {}: {detail}", result.year, result.day, result.part));
+    }
+
+    let regressions = results.iter().filter(|r| r.outcome.is_regression()).count();
+    output.push_str(&format!("\n\n{} checked, {regressions} regression(s)\n", results.len()));
+
+    output
+}
+
+/// Format benchmark statistics, with the change since the previous run
+pub fn format_bench(
+    stats: &crate::bench::BenchStats,
+    comparison: Option<&crate::bench::BenchComparison>,
+) -> String {
+    let mut output = format!("Runs:    {}\n", stats.runs);
+    output.push_str(&format!("Min:     {:.3}ms\n", stats.min_ms));
+    output.push_str(&format!("Median:  {:.3}ms\n", stats.median_ms));
+    output.push_str(&format!("Mean:    {:.3}ms ± {:.3}ms\n", stats.mean_ms, stats.stddev_ms));
+    if let Some(rss) = stats.peak_rss_kb {
+        output.push_str(&format!("Peak RSS: {:.1}MiB\n", rss as f64 / 1024.0));
+    }
+
+    if let Some(comparison) = comparison {
+        output.push_str(&format!(
+            "\nPrevious median {:.3}ms, change {:+.1}%{}\n",
+            comparison.previous_median_ms,
+            comparison.change_pct,
+            if comparison.regressed { "  ⚠ REGRESSION" } else { "" }
+        ));
+    }
+
+    output
+}
+
+/// Format a request preview as raw HTTP
+pub fn format_request_preview(request: &crate::models::RequestPreview) -> String {
+    let mut output = format!("{} {}\n", request.method, request.url);
+
+    for (name, value) in &request.headers {
+        output.push_str(&format!("{name}: {value}\n"));
+    }
+    output.push('\n');
+    output.push_str(&request.body);
+    output.push('\n');
+
+    output
+}
+
+/// Format the file writes skipped by a dry run
+pub fn format_planned_writes(planned: &[crate::models::PlannedWrite]) -> String {
+    if planned.is_empty() {
+        return "Dry run: no files would be written\n".to_string();
+    }
+
+    let mut output = String::from("Dry run: no files were written\n");
+
+    for write in planned {
+        output.push_str(&format!("  would {} {}\n", write.action.verb(), write.path.display()));
+        if let Some(diff) = &write.diff {
+            for line in diff.lines() {
+                output.push_str(&format!("    {line}\n"));
+            }
+        }
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +475,49 @@ R3,L2,R3,L1
         assert_eq!(answer, None);
     }
 
+    #[test]
+    fn test_split_parts() {
+        let banner = |n: i32| format!("\n\n{}\n PART {n} \n{}\n\n", "=".repeat(80), "=".repeat(80));
+        let description = format!("<p>one</p>{}<p>two</p>{}<p>three</p>", banner(2), banner(3));
+        let parts = split_parts(&description);
+        assert_eq!(parts, vec!["<p>one</p>", "<p>two</p>", "<p>three</p>"]);
+    }
+
+    #[test]
+    fn test_split_parts_single() {
+        assert_eq!(split_parts("<p>only</p>"), vec!["<p>only</p>"]);
+    }
+
+    #[test]
+    fn test_added_sections() {
+        let banner = format!("\n\n{}\n PART 2 \n{}\n\n", "=".repeat(80), "=".repeat(80));
+        let previous = "<h2>Quest 1</h2>\n<p>Intro</p>\n<pre>1 2</pre>";
+        let corrected = "<h2>Quest 1</h2>\n<p>Intro, corrected</p>\n<pre>1 2</pre>";
+        let current = format!("{corrected}{banner}<p>Part two</p>");
+
+        assert_eq!(
+            added_sections(previous, &current),
+            vec![
+                (1, "<p>Intro, corrected</p>".to_string()),
+                (2, "<p>Part two</p>".to_string()),
+            ]
+        );
+        assert!(added_sections(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_extract_title() {
+        let html = r#"<h2 id="title">Quest 5: <em>Pseudo-Random</em> Clap Dance</h2><p>Body</p>"#;
+        let title = extract_title(html);
+        assert_eq!(title, Some("Quest 5: Pseudo-Random Clap Dance".to_string()));
+    }
+
+    #[test]
+    fn test_extract_title_none() {
+        let html = r#"<p>No heading here</p>"#;
+        assert_eq!(extract_title(html), None);
+    }
+
     #[test]
     fn test_extract_expected_answer_last_match() {
         let html = r#"
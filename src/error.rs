@@ -1,21 +1,70 @@
 use thiserror::Error;
 
+/// Process exit codes, stable for shell automation (0 means success)
+pub mod exit_code {
+    /// Unclassified failure (file system, malformed data, ...)
+    pub const FAILURE: i32 = 1;
+    /// Invalid command-line arguments
+    pub const USAGE: i32 = 2;
+    /// Missing or rejected session cookie
+    pub const AUTH: i32 = 3;
+    /// Quest or part not unlocked yet
+    pub const NOT_UNLOCKED: i32 = 4;
+    /// Part already solved
+    pub const ALREADY_SOLVED: i32 = 5;
+    /// Submitted answer was incorrect
+    pub const WRONG_ANSWER: i32 = 6;
+    /// Server asked us to slow down
+    pub const RATE_LIMITED: i32 = 7;
+    /// Network failure or unexpected server response
+    pub const NETWORK: i32 = 8;
+    /// Downloaded content could not be decrypted
+    pub const DECRYPTION: i32 = 9;
+    /// A solver process was killed after exceeding its timeout
+    pub const TIMEOUT: i32 = 10;
+}
+
+/// Errors returned by the client, storage and crypto layers
 #[derive(Error, Debug)]
 pub enum EcError {
     #[error("Authentication failed: missing cookie")]
     MissingCookie,
 
+    #[error("Authentication failed: server returned {status} (is the cookie valid?)")]
+    AuthFailed { status: u16 },
+
     #[error("Decryption failed: {0}")]
     DecryptionError(String),
 
     #[error("API request failed: {0}")]
     ApiError(#[from] reqwest::Error),
 
-    #[error("Answer already submitted")]
-    AlreadySubmitted,
+    #[error("Answer already submitted for {year}/{day} part {part}")]
+    AlreadySubmitted { year: i32, day: i32, part: i32 },
+
+    #[error("Quest not available yet: {year}/{day}{}", part.map(|p| format!(" part {p}")).unwrap_or_default())]
+    QuestNotAvailable { year: i32, day: i32, part: Option<i32> },
+
+    #[error("Rate limited by server{}", retry_after.map(|s| format!(", retry after {s}s")).unwrap_or_default())]
+    RateLimited { retry_after: Option<u64> },
+
+    #[error("Invalid answer: {0}")]
+    InvalidAnswer(String),
 
-    #[error("Quest not available yet: {year}/{day} part {part}")]
-    QuestNotAvailable { year: i32, day: i32, part: i32 },
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Solver {reason}")]
+    SolverFailed { reason: String },
+
+    #[error("Solver timed out after {}s", timeout.as_secs_f64())]
+    SolverTimedOut { timeout: std::time::Duration },
+
+    #[error("Aborted by user")]
+    Aborted,
+
+    #[error("No {what} found at {}", path.display())]
+    MissingData { what: String, path: std::path::PathBuf },
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -30,4 +79,86 @@ pub enum EcError {
     HttpError { status: u16, message: String },
 }
 
+impl EcError {
+    /// Stable machine-readable code for structured output
+    pub fn code(&self) -> &'static str {
+        match self {
+            EcError::MissingCookie => "missing_cookie",
+            EcError::AuthFailed { .. } => "auth_failed",
+            EcError::DecryptionError(_) => "decryption_error",
+            EcError::ApiError(_) => "network_error",
+            EcError::AlreadySubmitted { .. } => "already_submitted",
+            EcError::QuestNotAvailable { .. } => "quest_not_available",
+            EcError::RateLimited { .. } => "rate_limited",
+            EcError::InvalidAnswer(_) => "invalid_answer",
+            EcError::InvalidArgument(_) => "invalid_argument",
+            EcError::SolverFailed { .. } => "solver_failed",
+            EcError::SolverTimedOut { .. } => "solver_timed_out",
+            EcError::Aborted => "aborted",
+            EcError::MissingData { .. } => "missing_data",
+            EcError::IoError(_) => "io_error",
+            EcError::JsonError(_) => "json_error",
+            EcError::HexError(_) => "hex_error",
+            EcError::HttpError { .. } => "http_error",
+        }
+    }
+
+    /// Process exit code for this error (see [`exit_code`])
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EcError::MissingCookie | EcError::AuthFailed { .. } => exit_code::AUTH,
+            EcError::QuestNotAvailable { .. } => exit_code::NOT_UNLOCKED,
+            EcError::AlreadySubmitted { .. } => exit_code::ALREADY_SOLVED,
+            EcError::RateLimited { .. } => exit_code::RATE_LIMITED,
+            EcError::ApiError(_) | EcError::HttpError { .. } => exit_code::NETWORK,
+            EcError::DecryptionError(_) | EcError::HexError(_) => exit_code::DECRYPTION,
+            EcError::InvalidAnswer(_) | EcError::InvalidArgument(_) => exit_code::USAGE,
+            EcError::SolverTimedOut { .. } => exit_code::TIMEOUT,
+            EcError::SolverFailed { .. }
+            | EcError::Aborted
+            | EcError::MissingData { .. }
+            | EcError::IoError(_)
+            | EcError::JsonError(_) => exit_code::FAILURE,
+        }
+    }
+
+    /// Classify a non-success HTTP status that has no endpoint-specific meaning
+    pub fn from_status(status: reqwest::StatusCode, retry_after: Option<u64>, message: String) -> Self {
+        match status.as_u16() {
+            401 | 403 => EcError::AuthFailed { status: status.as_u16() },
+            429 => EcError::RateLimited { retry_after },
+            code => EcError::HttpError { status: code, message },
+        }
+    }
+}
+
+/// Result alias used throughout the crate
 pub type Result<T> = std::result::Result<T, EcError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_from_status_classifies_auth_and_rate_limit() {
+        let auth = EcError::from_status(StatusCode::FORBIDDEN, None, String::new());
+        assert_eq!(auth.exit_code(), exit_code::AUTH);
+
+        let limited = EcError::from_status(StatusCode::TOO_MANY_REQUESTS, Some(30), String::new());
+        assert_eq!(limited.exit_code(), exit_code::RATE_LIMITED);
+        assert_eq!(limited.to_string(), "Rate limited by server, retry after 30s");
+
+        let other = EcError::from_status(StatusCode::BAD_GATEWAY, None, "boom".to_string());
+        assert_eq!(other.exit_code(), exit_code::NETWORK);
+    }
+
+    #[test]
+    fn test_quest_not_available_message() {
+        let day = EcError::QuestNotAvailable { year: 2024, day: 5, part: None };
+        assert_eq!(day.to_string(), "Quest not available yet: 2024/5");
+
+        let part = EcError::QuestNotAvailable { year: 2024, day: 5, part: Some(2) };
+        assert_eq!(part.to_string(), "Quest not available yet: 2024/5 part 2");
+    }
+}
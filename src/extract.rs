@@ -0,0 +1,390 @@
+//! Sample extraction from quest descriptions
+//!
+//! Descriptions are parsed into a DOM rather than matched with regexes, so
+//! attribute order, extra classes, nested tags and HTML entities don't matter.
+//! Two kinds of blocks are recognised, in document order:
+//!
+//! - **Sample inputs**: `<pre>` elements with a `note` class
+//! - **Expected answers**: `<pre>` elements, or `<p>` elements wrapping
+//!   `<code>`, whose whole text is a single `<b>`/`<strong>` element
+//!
+//! An answer belongs to the closest sample before it. When several answers
+//! follow one sample, the last one wins: quests show intermediate results
+//! before the final answer. Samples followed by another sample or by nothing
+//! stay unpaired.
+//!
+//! When that guess is wrong, `ec-cli samples` lets the user pick the blocks by
+//! hand; the picks are stored as [`SamplePick`]s and applied instead.
+
+use scraper::{CaseSensitivity::AsciiCaseInsensitive, ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::display;
+use crate::error::{EcError, Result};
+
+/// An example input from a description, with its answer when one was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Sample {
+    /// Part the sample belongs to (1-3)
+    pub part: i32,
+    /// Position of the sample within its part, starting at 1
+    pub index: usize,
+    pub input: String,
+    pub expected_answer: Option<String>,
+    /// Text of the paragraph introducing the sample
+    pub context_text: Option<String>,
+}
+
+/// Extract the samples of every part of a combined description
+pub fn extract_samples(description: &str) -> Vec<Sample> {
+    (1..)
+        .zip(display::split_parts(description))
+        .flat_map(|(part, html)| extract_part_samples(part, html))
+        .collect()
+}
+
+/// Extract the samples of a single part's HTML
+pub fn extract_part_samples(part: i32, html: &str) -> Vec<Sample> {
+    let mut samples: Vec<Sample> = Vec::new();
+
+    for block in blocks(html) {
+        match block {
+            Block::Sample { input, context_text } => samples.push(Sample {
+                part,
+                index: samples.len() + 1,
+                input,
+                expected_answer: None,
+                context_text,
+            }),
+            Block::Answer(answer) => {
+                if let Some(sample) = samples.last_mut() {
+                    sample.expected_answer = Some(answer);
+                }
+            }
+        }
+    }
+
+    samples
+}
+
+/// Text of every sample input block, in order
+pub fn sample_inputs(html: &str) -> Vec<String> {
+    blocks(html)
+        .into_iter()
+        .filter_map(|block| match block {
+            Block::Sample { input, .. } => Some(input),
+            Block::Answer(_) => None,
+        })
+        .collect()
+}
+
+/// Text of every expected answer block, in order
+pub fn answers(html: &str) -> Vec<String> {
+    blocks(html)
+        .into_iter()
+        .filter_map(|block| match block {
+            Block::Answer(answer) => Some(answer),
+            Block::Sample { .. } => None,
+        })
+        .collect()
+}
+
+/// A `<pre>` element of a part, as offered by the sample picker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreBlock {
+    /// Position among the part's `<pre>` elements, starting at 1
+    pub number: usize,
+    pub text: String,
+    /// What the automatic extraction took the block for
+    pub guess: Option<BlockGuess>,
+    pub context_text: Option<String>,
+}
+
+/// Automatic classification of a `<pre>` block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockGuess {
+    Sample,
+    Answer,
+}
+
+/// A sample chosen by hand: input blocks (joined in order) and an answer block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SamplePick {
+    pub inputs: Vec<usize>,
+    pub answer: Option<usize>,
+}
+
+impl std::str::FromStr for SamplePick {
+    type Err = String;
+
+    /// Parse `INPUT[+INPUT...][:ANSWER]`, e.g. `2`, `1+2:4` or `3:5`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (inputs, answer) = match s.split_once(':') {
+            Some((inputs, answer)) => (inputs, Some(answer)),
+            None => (s, None),
+        };
+        let number = |n: &str| -> std::result::Result<usize, String> {
+            n.trim()
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("invalid block number {n:?} in {s:?}"))
+        };
+
+        Ok(SamplePick {
+            inputs: inputs.split('+').map(number).collect::<std::result::Result<_, _>>()?,
+            answer: answer
+                .filter(|a| !a.trim().is_empty())
+                .map(number)
+                .transpose()?,
+        })
+    }
+}
+
+/// Every `<pre>` element of a part's HTML, in order
+pub fn pre_blocks(html: &str) -> Vec<PreBlock> {
+    let document = Html::parse_fragment(html);
+    let pre = Selector::parse("pre").unwrap();
+
+    (1..)
+        .zip(document.select(&pre))
+        .map(|(number, element)| PreBlock {
+            number,
+            text: element.text().collect::<String>().trim_start().to_string(),
+            guess: if is_sample(element) {
+                Some(BlockGuess::Sample)
+            } else {
+                answer_text(element).map(|_| BlockGuess::Answer)
+            },
+            context_text: context_text(element),
+        })
+        .collect()
+}
+
+/// Build a part's samples from hand-picked `<pre>` blocks
+pub fn apply_picks(part: i32, html: &str, picks: &[SamplePick]) -> Result<Vec<Sample>> {
+    let blocks = pre_blocks(html);
+    let block = |n: usize| {
+        blocks.get(n.wrapping_sub(1)).ok_or_else(|| {
+            EcError::InvalidArgument(format!(
+                "part {part} has no <pre> block {n} (it has {})",
+                blocks.len()
+            ))
+        })
+    };
+
+    (1..)
+        .zip(picks)
+        .map(|(index, pick)| {
+            let inputs = pick
+                .inputs
+                .iter()
+                .map(|&n| block(n).map(|b| b.text.as_str()))
+                .collect::<Result<Vec<_>>>()?;
+            let first = block(pick.inputs.first().copied().unwrap_or(0))?;
+
+            Ok(Sample {
+                part,
+                index,
+                input: join_blocks(&inputs),
+                expected_answer: pick
+                    .answer
+                    .map(|n| block(n).map(|b| collapse_whitespace(&b.text)))
+                    .transpose()?,
+                context_text: first.context_text.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Join several input blocks with a blank line, as the description shows them
+fn join_blocks(blocks: &[&str]) -> String {
+    blocks
+        .iter()
+        .map(|b| b.trim_end_matches('\n'))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        + "\n"
+}
+
+enum Block {
+    Sample {
+        input: String,
+        context_text: Option<String>,
+    },
+    Answer(String),
+}
+
+fn blocks(html: &str) -> Vec<Block> {
+    let document = Html::parse_fragment(html);
+    let candidates = Selector::parse("pre, p").unwrap();
+
+    document
+        .select(&candidates)
+        .filter_map(|element| {
+            if is_sample(element) {
+                Some(Block::Sample {
+                    input: element.text().collect::<String>().trim_start().to_string(),
+                    context_text: context_text(element),
+                })
+            } else {
+                answer_text(element).map(Block::Answer)
+            }
+        })
+        .collect()
+}
+
+fn is_sample(element: ElementRef) -> bool {
+    element.value().name() == "pre" && element.value().has_class("note", AsciiCaseInsensitive)
+}
+
+/// The answer if the element's only content is one bold element
+fn answer_text(element: ElementRef) -> Option<String> {
+    let bold = Selector::parse("b, strong").unwrap();
+    let code = Selector::parse("code").unwrap();
+
+    if element.value().name() == "p" && element.select(&code).next().is_none() {
+        return None;
+    }
+
+    let mut bolds = element.select(&bold);
+    let answer = bolds.next()?;
+    if bolds.next().is_some() {
+        return None;
+    }
+
+    let answer = collapse_whitespace(&answer.text().collect::<String>());
+    let whole = collapse_whitespace(&element.text().collect::<String>());
+    (!answer.is_empty() && answer == whole).then_some(answer)
+}
+
+/// Text of the closest element before the sample, usually its introduction
+fn context_text(element: ElementRef) -> Option<String> {
+    element
+        .prev_siblings()
+        .find_map(ElementRef::wrap)
+        .map(|previous| collapse_whitespace(&previous.text().collect::<String>()))
+        .filter(|text| !text.is_empty())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASIC: &str = include_str!("../tests/fixtures/descriptions/basic.html");
+    const MULTIPLE: &str = include_str!("../tests/fixtures/descriptions/multiple.html");
+    const VARIANTS: &str = include_str!("../tests/fixtures/descriptions/variants.html");
+    const INTERMEDIATE: &str = include_str!("../tests/fixtures/descriptions/intermediate.html");
+
+    type Summary = (i32, usize, String, Option<String>);
+
+    fn sample(part: i32, index: usize, input: &str, answer: Option<&str>) -> Summary {
+        (part, index, input.to_string(), answer.map(str::to_string))
+    }
+
+    fn summary(samples: &[Sample]) -> Vec<Summary> {
+        samples
+            .iter()
+            .map(|s| (s.part, s.index, s.input.clone(), s.expected_answer.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_basic_one_sample_per_part() {
+        let samples = extract_samples(BASIC);
+        assert_eq!(
+            summary(&samples),
+            vec![
+                sample(1, 1, "ABBAC\n", Some("5")),
+                sample(2, 1, "AxBCDDCAxD\n", Some("28")),
+                sample(3, 1, "xBxAAABCDxCC\n", Some("30")),
+            ]
+        );
+        assert_eq!(
+            samples[0].context_text.as_deref(),
+            Some("Example based on the following notes:")
+        );
+    }
+
+    #[test]
+    fn test_multiple_samples_pair_with_following_answer() {
+        let samples = extract_samples(MULTIPLE);
+        assert_eq!(
+            summary(&samples),
+            vec![
+                sample(1, 1, "A:+,-,=,=\nB:+,=,-,+\nC:=,-,+,+\n", Some("BDCA")),
+                sample(1, 2, "A:+,+,+\nB:-,-,-\n", Some("AB")),
+                sample(2, 1, "S+===\n-   +\n=+=-+\n", None),
+                sample(2, 2, "A:+,-,=,=\nB:+,=,-,+\n", Some("DCBA")),
+            ]
+        );
+        assert_eq!(
+            samples[2].context_text.as_deref(),
+            Some("The track used in the examples below:")
+        );
+    }
+
+    #[test]
+    fn test_markup_variants() {
+        let samples = extract_samples(VARIANTS);
+        assert_eq!(
+            summary(&samples),
+            vec![
+                sample(1, 1, ".T.T\n<#>\n.B.\n", Some("13")),
+                sample(2, 1, "ABC\nDEF", Some("A1B2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_last_answer_after_a_sample_wins() {
+        let samples = extract_samples(INTERMEDIATE);
+        assert_eq!(
+            summary(&samples),
+            vec![sample(1, 1, "3\n5\n2", Some("31")), sample(1, 2, "1\n1", Some("4"))]
+        );
+    }
+
+    #[test]
+    fn test_pre_blocks_and_picks() {
+        let part2 = display::split_parts(MULTIPLE)[1];
+        let blocks = pre_blocks(part2);
+        let guesses: Vec<_> = blocks.iter().map(|b| b.guess).collect();
+        assert_eq!(
+            guesses,
+            vec![Some(BlockGuess::Sample), Some(BlockGuess::Sample), Some(BlockGuess::Answer)]
+        );
+
+        let pick: SamplePick = "1+2:3".parse().unwrap();
+        let samples = apply_picks(2, part2, &[pick]).unwrap();
+        assert_eq!(samples[0].input, "S+===\n-   +\n=+=-+\n\nA:+,-,=,=\nB:+,=,-,+\n");
+        assert_eq!(samples[0].expected_answer.as_deref(), Some("DCBA"));
+
+        assert!(apply_picks(2, part2, &["4".parse().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_sample_pick() {
+        assert_eq!(
+            "2".parse::<SamplePick>().unwrap(),
+            SamplePick { inputs: vec![2], answer: None }
+        );
+        assert_eq!(
+            "1+2:4".parse::<SamplePick>().unwrap(),
+            SamplePick { inputs: vec![1, 2], answer: Some(4) }
+        );
+        assert!("0:1".parse::<SamplePick>().is_err());
+        assert!("a".parse::<SamplePick>().is_err());
+    }
+
+    #[test]
+    fn test_bold_inside_prose_is_not_an_answer() {
+        let html = r#"<pre class="note">x</pre><p>The <b>answer</b> is <code>4</code>.</p><pre>plain</pre>"#;
+        assert_eq!(extract_part_samples(1, html)[0].expected_answer, None);
+    }
+}
@@ -0,0 +1,316 @@
+//! Run registered [`Solution`]s against stored samples and inputs
+//!
+//! A solutions crate registers its quests and hands control to the harness:
+//!
+//! ```no_run
+//! # use ec_cli::Solution;
+//! # struct Quest1;
+//! # impl Solution for Quest1 {
+//! #     type Parsed = String;
+//! #     fn parse(input: &str) -> String { input.to_string() }
+//! # }
+//! fn main() {
+//!     ec_cli::Harness::new()
+//!         .register::<Quest1>(2024, 1)
+//!         .main();
+//! }
+//! ```
+//!
+//! `ec-cli run` builds and runs that binary with `cargo run --release`,
+//! passing the selection as arguments (`--all`, `--year`, `--day`, `--part`,
+//! `--sample`).
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::display;
+use crate::error::Result;
+use crate::inputs;
+use crate::solution::Solution;
+use crate::storage::Storage;
+
+type Solver = Box<dyn Fn(i32, &str) -> Option<String>>;
+
+/// Which data a run used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunKind {
+    Sample,
+    Input,
+}
+
+/// Outcome of comparing an answer with the stored reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Matches the expected sample answer or an accepted submission
+    Pass,
+    /// Differs from the reference answer
+    Fail,
+    /// No reference answer is stored yet
+    Unverified,
+    /// The part isn't implemented or its data isn't stored
+    Skipped,
+}
+
+/// Result of running one part on one data set
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub year: i32,
+    pub day: i32,
+    pub part: i32,
+    pub kind: RunKind,
+    /// Number of the sample, for sample runs
+    pub sample: Option<usize>,
+    pub answer: Option<String>,
+    pub expected: Option<String>,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    pub verdict: Verdict,
+}
+
+/// Serialize a duration as fractional milliseconds
+pub(crate) fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// Which quests and parts to run
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub year: Option<i32>,
+    pub day: Option<i32>,
+    pub part: Option<i32>,
+    /// Only run this sample of each part
+    pub sample: Option<usize>,
+}
+
+impl Selection {
+    /// Parse harness arguments (`--all`, `--year Y`, `--day D`, `--part P`, `--sample N`)
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> std::result::Result<Self, String> {
+        let mut selection = Selection::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| -> std::result::Result<i32, String> {
+                args.next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| format!("{name} expects a number"))
+            };
+            match arg.as_str() {
+                "--all" => {}
+                "-y" | "--year" => selection.year = Some(value("--year")?),
+                "-d" | "--day" => selection.day = Some(value("--day")?),
+                "-p" | "--part" => selection.part = Some(value("--part")?),
+                "-s" | "--sample" => selection.sample = Some(value("--sample")? as usize),
+                other => return Err(format!("Unknown argument: {other}")),
+            }
+        }
+
+        Ok(selection)
+    }
+
+    fn matches(&self, year: i32, day: i32, part: i32) -> bool {
+        self.year.is_none_or(|y| y == year)
+            && self.day.is_none_or(|d| d == day)
+            && self.part.is_none_or(|p| p == part)
+    }
+}
+
+/// Registry of solutions plus the storage they are run against
+pub struct Harness {
+    storage: Storage,
+    solutions: Vec<(i32, i32, Solver)>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// Create a harness using the data directory resolved by [`inputs::base_path`]
+    pub fn new() -> Self {
+        Self::with_storage(inputs::storage())
+    }
+
+    /// Create a harness reading from a specific storage layout
+    pub fn with_storage(storage: Storage) -> Self {
+        Self {
+            storage,
+            solutions: Vec::new(),
+        }
+    }
+
+    /// Register the solution for a quest
+    pub fn register<S: Solution + 'static>(mut self, year: i32, day: i32) -> Self {
+        self.solutions.push((year, day, Box::new(S::solve)));
+        self
+    }
+
+    /// Run every selected part on its samples and its input
+    pub fn run(&self, selection: &Selection) -> Result<Vec<RunReport>> {
+        let mut reports = Vec::new();
+
+        for (year, day, solver) in &self.solutions {
+            let (year, day) = (*year, *day);
+            let accepted = self.storage.load_submissions(year, day)?;
+
+            for part in (1..=3).filter(|&p| selection.matches(year, day, p)) {
+                let samples: Vec<usize> = self
+                    .storage
+                    .sample_numbers(year, day, part)
+                    .into_iter()
+                    .filter(|&n| selection.sample.is_none_or(|s| s == n))
+                    .collect();
+                if samples.is_empty() {
+                    reports.push(run_one(solver, year, day, part, RunKind::Sample, None, None));
+                }
+                for n in samples {
+                    let sample = self.storage.load_sample(year, day, part, n)?;
+                    let expected = self.storage.load_expected_answer(year, day, part, n)?;
+                    let mut report =
+                        run_one(solver, year, day, part, RunKind::Sample, Some(sample), expected);
+                    report.sample = Some(n);
+                    reports.push(report);
+                }
+
+                let input = self
+                    .storage
+                    .has_input(year, day, part)
+                    .then(|| self.storage.load_input(year, day, part))
+                    .transpose()?;
+                let correct = accepted
+                    .iter()
+                    .rfind(|s| s.part == part && s.correct)
+                    .map(|s| s.answer.clone());
+                reports.push(run_one(solver, year, day, part, RunKind::Input, input, correct));
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Entry point for solution binaries
+    ///
+    /// Reads the selection from the command line, prints a report (JSON when
+    /// `EC_FORMAT=json`) and exits non-zero if any answer is wrong.
+    pub fn main(self) {
+        let selection = match Selection::from_args(std::env::args().skip(1)) {
+            Ok(selection) => selection,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(crate::error::exit_code::USAGE);
+            }
+        };
+
+        let reports = match self.run(&selection) {
+            Ok(reports) => reports,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(e.exit_code());
+            }
+        };
+
+        if std::env::var("EC_FORMAT").as_deref() == Ok("json") {
+            println!("{}", serde_json::to_string_pretty(&reports).unwrap_or_default());
+        } else {
+            print!("{}", display::format_run_reports(&reports));
+        }
+
+        if reports.iter().any(|r| r.verdict == Verdict::Fail) {
+            std::process::exit(crate::error::exit_code::WRONG_ANSWER);
+        }
+    }
+}
+
+fn run_one(
+    solver: &Solver,
+    year: i32,
+    day: i32,
+    part: i32,
+    kind: RunKind,
+    data: Option<String>,
+    expected: Option<String>,
+) -> RunReport {
+    let start = Instant::now();
+    let answer = data.and_then(|data| solver(part, &data));
+    let duration = start.elapsed();
+
+    let verdict = match (&answer, &expected) {
+        (None, _) => Verdict::Skipped,
+        (Some(_), None) => Verdict::Unverified,
+        (Some(answer), Some(expected)) if answer.trim() == expected.trim() => Verdict::Pass,
+        (Some(_), Some(_)) => Verdict::Fail,
+    };
+
+    RunReport {
+        year,
+        day,
+        part,
+        kind,
+        sample: None,
+        answer,
+        expected,
+        duration,
+        verdict,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Parsed = Vec<i64>;
+
+        fn parse(input: &str) -> Self::Parsed {
+            input.lines().filter_map(|l| l.trim().parse().ok()).collect()
+        }
+
+        fn part1(numbers: &Self::Parsed) -> Option<String> {
+            Some(numbers.iter().sum::<i64>().to_string())
+        }
+    }
+
+    #[test]
+    fn test_harness_checks_samples_and_skips_missing_parts() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        storage.save_sample(2024, 1, 1, 1, "1\n2\n3\n").unwrap();
+        storage.save_expected_answer(2024, 1, 1, 1, "6").unwrap();
+        storage.save_sample(2024, 1, 1, 2, "1\n").unwrap();
+        storage.save_expected_answer(2024, 1, 1, 2, "2").unwrap();
+        storage.save_sample(2024, 1, 2, 1, "4\n").unwrap();
+
+        let harness = Harness::with_storage(Storage::new(Some(base.clone()))).register::<Sum>(2024, 1);
+        let reports = harness.run(&Selection::default()).unwrap();
+
+        let sample1 = &reports[0];
+        assert_eq!((sample1.part, sample1.kind, sample1.sample), (1, RunKind::Sample, Some(1)));
+        assert_eq!(sample1.verdict, Verdict::Pass);
+        assert_eq!((reports[1].sample, reports[1].verdict), (Some(2), Verdict::Fail));
+        assert_eq!(reports[2].verdict, Verdict::Skipped); // no input stored
+        assert_eq!(reports[3].verdict, Verdict::Skipped); // part 2 not implemented
+
+        let only_first = Selection { sample: Some(1), ..Selection::default() };
+        let reports = harness.run(&only_first).unwrap();
+        assert_eq!(reports.iter().filter(|r| r.kind == RunKind::Sample && r.part == 1).count(), 1);
+    }
+
+    #[test]
+    fn test_selection_from_args() {
+        let args = ["--day", "5", "--part", "2", "--sample", "3"].map(String::from);
+        let selection = Selection::from_args(args).unwrap();
+        assert_eq!(selection.sample, Some(3));
+        assert!(selection.matches(2024, 5, 2));
+        assert!(!selection.matches(2024, 5, 1));
+        assert!(Selection::from_args(["--bogus".to_string()]).is_err());
+    }
+}
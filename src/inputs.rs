@@ -0,0 +1,258 @@
+//! Helpers for loading puzzle data from Rust solutions
+//!
+//! ```no_run
+//! let input = ec_cli::input(2024, 5, 1)?;
+//! let sample = ec_cli::sample(2024, 5, 1)?;
+//! let expected = ec_cli::expected(2024, 5, 1)?;
+//! # Ok::<(), ec_cli::EcError>(())
+//! ```
+//!
+//! Files are resolved against the same layout the CLI writes. The base
+//! directory is `EC_BASE_PATH` when set, otherwise the nearest `data`
+//! directory in the current directory or one of its ancestors, so solutions
+//! can run from anywhere inside a workspace. Missing files are fetched on
+//! demand through [`EcClient`] when the `solver` feature is enabled.
+//!
+//! The helpers are blocking. Fetching runs on a private Tokio runtime, so a
+//! missing file can't be fetched from code that is already running inside a
+//! runtime; that returns an error, and async code should use [`EcClient`]
+//! directly.
+
+use log::info;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::error::{EcError, Result};
+use crate::storage::Storage;
+#[cfg(feature = "solver")]
+use crate::EcClient;
+
+/// Environment variable overriding the base data directory
+pub const BASE_PATH_ENV: &str = "EC_BASE_PATH";
+
+/// Resolve the base data directory used by the helpers
+pub fn base_path() -> PathBuf {
+    if let Some(path) = env::var_os(BASE_PATH_ENV) {
+        return PathBuf::from(path);
+    }
+
+    env::current_dir()
+        .ok()
+        .and_then(|cwd| find_data_dir(&cwd))
+        .unwrap_or_else(|| PathBuf::from("data"))
+}
+
+/// The `data` directory in `dir` or its nearest ancestor that has one
+fn find_data_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("data"))
+        .find(|candidate| candidate.is_dir())
+}
+
+/// Storage rooted at [`base_path`]
+pub fn storage() -> Storage {
+    Storage::new(Some(base_path()))
+}
+
+/// Puzzle input for a part, fetched if it isn't stored yet
+pub fn input(year: i32, day: i32, part: i32) -> Result<String> {
+    load_input(&storage(), year, day, part, true)
+}
+
+/// First sample input of a part, fetched with the description if missing
+pub fn sample(year: i32, day: i32, part: i32) -> Result<String> {
+    sample_n(year, day, part, 1)
+}
+
+/// Expected answer for a part's first sample, fetched with the description if missing
+pub fn expected(year: i32, day: i32, part: i32) -> Result<String> {
+    expected_n(year, day, part, 1)
+}
+
+/// The `n`th sample input of a part (numbered from 1)
+pub fn sample_n(year: i32, day: i32, part: i32, n: usize) -> Result<String> {
+    load_sample(&storage(), year, day, part, n, true)
+}
+
+/// Expected answer for the `n`th sample of a part
+pub fn expected_n(year: i32, day: i32, part: i32, n: usize) -> Result<String> {
+    load_expected(&storage(), year, day, part, n, true)
+}
+
+fn load_input(storage: &Storage, year: i32, day: i32, part: i32, fetch: bool) -> Result<String> {
+    if fetch && !storage.has_input(year, day, part) {
+        fetch_input(storage, year, day, part)?;
+    }
+    storage.load_input(year, day, part)
+}
+
+fn load_sample(storage: &Storage, year: i32, day: i32, part: i32, n: usize, fetch: bool) -> Result<String> {
+    if fetch && !storage.has_sample(year, day, part) {
+        fetch_samples(storage, year, day)?;
+    }
+    storage.load_sample(year, day, part, n)
+}
+
+fn load_expected(storage: &Storage, year: i32, day: i32, part: i32, n: usize, fetch: bool) -> Result<String> {
+    if let Some(answer) = storage.load_expected_answer(year, day, part, n)? {
+        return Ok(answer);
+    }
+
+    // A stored sample without an answer is normal (the description never
+    // paired one), and fetching again wouldn't change that
+    if fetch && !(storage.has_description(year, day) && storage.has_sample(year, day, part)) {
+        fetch_samples(storage, year, day)?;
+    }
+    storage
+        .load_expected_answer(year, day, part, n)?
+        .ok_or_else(|| EcError::MissingData {
+            what: format!("expected answer for sample {n}"),
+            path: storage.expected_answer_file(year, day, part, n),
+        })
+}
+
+#[cfg(feature = "solver")]
+fn fetch_input(storage: &Storage, year: i32, day: i32, part: i32) -> Result<()> {
+    info!("Input for {year}/{day} part {part} not stored, fetching...");
+    block_on(async {
+        let mut client = EcClient::new()?;
+        let input = client.fetch_input(year, day, part).await?;
+        storage.save_input(year, day, part, &input)?;
+        Ok(())
+    })
+}
+
+#[cfg(feature = "solver")]
+fn fetch_samples(storage: &Storage, year: i32, day: i32) -> Result<()> {
+    info!("Samples for {year}/{day} not stored, fetching description...");
+    block_on(async {
+        let client = EcClient::new()?;
+        let description = client.fetch_description(year, day).await?;
+        storage.save_description(year, day, &description)?;
+        storage.save_samples(year, day, &description, None)?;
+        Ok(())
+    })
+}
+
+/// Run a future to completion on a private single-threaded runtime
+///
+/// Tokio panics when a runtime is started inside another, so that case is
+/// reported as an error instead.
+#[cfg(feature = "solver")]
+fn block_on<F: std::future::Future<Output = Result<()>>>(future: F) -> Result<()> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(EcError::InvalidArgument(
+            "missing puzzle data can't be fetched by the blocking helpers inside an async runtime; use EcClient instead"
+                .to_string(),
+        ));
+    }
+
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(future)
+}
+
+#[cfg(not(feature = "solver"))]
+fn fetch_input(storage: &Storage, year: i32, day: i32, part: i32) -> Result<()> {
+    info!("Fetching on demand requires the `solver` feature");
+    Err(EcError::MissingData {
+        what: "input".to_string(),
+        path: storage.input_file(year, day, part),
+    })
+}
+
+#[cfg(not(feature = "solver"))]
+fn fetch_samples(_storage: &Storage, _year: i32, _day: i32) -> Result<()> {
+    info!("Fetching on demand requires the `solver` feature");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_base_path_override_and_nearest_data_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let nested = base.join("solutions").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        assert_ne!(find_data_dir(&nested), Some(base.join("data")));
+
+        fs::create_dir_all(base.join("data")).unwrap();
+        assert_eq!(find_data_dir(&nested), Some(base.join("data")));
+        assert_eq!(find_data_dir(&base), Some(base.join("data")));
+
+        // No other test reads this variable
+        env::set_var(BASE_PATH_ENV, &nested);
+        assert_eq!(base_path(), nested);
+        env::remove_var(BASE_PATH_ENV);
+    }
+
+    #[test]
+    fn test_loads_stored_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        storage.save_input(2024, 3, 1, "input\n").unwrap();
+        storage.save_sample(2024, 3, 1, 1, "first").unwrap();
+        storage.save_sample(2024, 3, 1, 2, "second").unwrap();
+        storage.save_expected_answer(2024, 3, 1, 2, " 42\n").unwrap();
+
+        assert_eq!(load_input(&storage, 2024, 3, 1, false).unwrap(), "input\n");
+        assert_eq!(load_sample(&storage, 2024, 3, 1, 1, false).unwrap(), "first");
+        assert_eq!(load_sample(&storage, 2024, 3, 1, 2, false).unwrap(), "second");
+        assert_eq!(load_expected(&storage, 2024, 3, 1, 2, false).unwrap(), "42");
+    }
+
+    #[test]
+    fn test_missing_files_name_the_path() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("data");
+        let storage = Storage::new(Some(base.clone()));
+
+        let err = load_input(&storage, 2024, 3, 2, false).unwrap_err();
+        assert!(matches!(err, EcError::MissingData { .. }));
+        assert_eq!(
+            err.to_string(),
+            format!("No input found at {}", storage.input_file(2024, 3, 2).display())
+        );
+
+        let err = load_sample(&storage, 2024, 3, 2, 1, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("No sample found at {}", storage.sample_file(2024, 3, 2, 1).display())
+        );
+
+        let err = load_expected(&storage, 2024, 3, 2, 2, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "No expected answer for sample 2 found at {}",
+                storage.expected_answer_file(2024, 3, 2, 2).display()
+            )
+        );
+        assert!(!base.exists());
+    }
+
+    #[test]
+    fn test_unpaired_sample_is_not_refetched() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        storage.save_description(2024, 3, "<pre class=\"note\">1 2</pre>").unwrap();
+        storage.save_sample(2024, 3, 1, 1, "1 2").unwrap();
+
+        // Fetching would fail without a cookie or network, with a different error
+        let err = load_expected(&storage, 2024, 3, 1, 1, true).unwrap_err();
+        assert!(matches!(err, EcError::MissingData { .. }), "{err}");
+    }
+
+    #[cfg(feature = "solver")]
+    #[tokio::test]
+    async fn test_fetching_inside_a_runtime_is_an_error() {
+        let err = block_on(async { Ok(()) }).unwrap_err();
+        assert!(matches!(err, EcError::InvalidArgument(_)));
+    }
+}
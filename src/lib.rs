@@ -0,0 +1,66 @@
+//! Client library for [Everybody Codes](https://everybody.codes) puzzles.
+//!
+//! This crate powers the `ec-cli` binary and can be used directly from
+//! solution workspaces:
+//!
+//! - [`EcClient`] talks to the API and decrypts inputs and descriptions
+//! - [`Storage`] reads and writes the on-disk layout (`{base}/{year}/inputs/...`)
+//! - [`display`] extracts samples and renders descriptions as text
+//! - [`crypto::decrypt_aes_cbc`] implements the site's AES-CBC scheme
+//! - [`input`], [`sample`] and [`expected`] load puzzle data from solutions
+//! - [`Solution`] and [`Harness`] run solvers against stored data
+//! - [`solver::SolverCommand`] runs external solver programs
+//! - [`regress`], [`bench`](mod@bench) and [`next`] back the matching `ec-cli` commands
+//!
+//! All fallible operations return [`error::Result`] with a typed [`EcError`].
+//!
+//! ```no_run
+//! use ec_cli::{EcClient, Storage};
+//!
+//! # async fn run() -> ec_cli::error::Result<()> {
+//! let mut client = EcClient::new()?;
+//! let input = client.fetch_input(2024, 5, 1).await?;
+//!
+//! let storage = Storage::new(None);
+//! storage.save_input(2024, 5, 1, &input)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Build with `default-features = false` to leave out the CLI-only
+//! dependencies (clap, env_logger, term_size, toml_edit, glob) and the
+//! [`archive`], [`scaffold`] and [`watch`] modules. Enable the `solver` feature
+//! on top of that to let [`input`] and friends fetch missing files.
+
+pub mod answer;
+#[cfg(feature = "cli")]
+pub mod archive;
+pub mod bench;
+pub mod client;
+pub mod crypto;
+pub mod display;
+pub mod error;
+pub mod extract;
+pub mod harness;
+pub mod inputs;
+pub mod markdown;
+pub mod models;
+pub mod next;
+pub mod regress;
+pub mod render;
+#[cfg(feature = "cli")]
+pub mod scaffold;
+pub mod search;
+pub mod solution;
+pub mod solver;
+pub mod storage;
+#[cfg(feature = "cli")]
+pub mod watch;
+
+pub use client::EcClient;
+pub use crypto::decrypt_aes_cbc;
+pub use error::{EcError, Result};
+pub use harness::Harness;
+pub use inputs::{expected, input, sample};
+pub use solution::Solution;
+pub use storage::Storage;
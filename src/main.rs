@@ -1,17 +1,22 @@
 mod cli;
-mod client;
-mod crypto;
-mod display;
-mod error;
-mod models;
-mod storage;
+mod pager;
 
 use clap::Parser;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
+use serde_json::{json, Value};
+use std::io::IsTerminal;
 
-use crate::cli::{Cli, Commands};
-use crate::client::EcClient;
-use crate::storage::Storage;
+use ec_cli::answer::{self, AnswerSource};
+use ec_cli::error::{self, exit_code, EcError};
+use ec_cli::extract::{self, SamplePick};
+use ec_cli::models::{PartStatus, QuestStatus, Submission};
+use ec_cli::solver::{self, SolverCommand, SolverLimits, SolverStatus};
+use ec_cli::{archive, bench, markdown, next, regress, render, search};
+use ec_cli::scaffold::{self, ScaffoldAction};
+use ec_cli::watch::{self, FileWatcher};
+use ec_cli::{display, EcClient, Storage};
+
+use crate::cli::{Cli, Commands, ExportFormat, OutputFormat};
 
 #[tokio::main]
 async fn main() {
@@ -27,12 +32,23 @@ async fn main() {
     };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
 
+    let format = cli.format;
+
     // Validate arguments
     if let Err(e) = cli.validate() {
         error!("{e}");
-        std::process::exit(1);
+        if format == OutputFormat::Json {
+            print_json(&json!({
+                "ok": false,
+                "error": { "code": "invalid_argument", "message": e },
+            }));
+        }
+        std::process::exit(exit_code::USAGE);
     }
 
+    let command = cli.command.name();
+    let mut storage = Storage::new(cli.base_path.map(|p| p.into())).with_dry_run(cli.dry_run);
+
     // Execute command
     let result = match cli.command {
         Commands::Fetch {
@@ -46,131 +62,244 @@ async fn main() {
             sample_path,
             sample_answer_path,
         } => {
+            // Track if custom sample/answer paths are used
+            let use_custom_paths = sample_path.is_some() || sample_answer_path.is_some();
+
+            if let Some(desc_path) = description_path {
+                storage = storage.with_description_path(desc_path.into());
+            }
+
+            if let Some(inp_path) = input_path {
+                storage = storage.with_input_path(inp_path.into());
+            }
+
+            if let Some(samp_path) = sample_path {
+                storage = storage.with_sample_path(samp_path.into());
+            }
+
+            if let Some(samp_ans_path) = sample_answer_path {
+                storage = storage.with_sample_answer_path(samp_ans_path.into());
+            }
+
             handle_fetch(
-                cli.base_path.clone(),
+                &storage,
                 year,
                 day,
                 part,
                 description_only,
                 input_only,
-                description_path,
-                input_path,
-                sample_path,
-                sample_answer_path,
+                use_custom_paths,
             )
             .await
         }
-        Commands::Read { year, day, width } => {
-            handle_read(cli.base_path.clone(), year, day, width).await
+        Commands::Read {
+            year,
+            day,
+            width,
+            part,
+            latest,
+            new,
+            plain,
+            no_pager,
+        } => {
+            let options = ReadOptions {
+                width,
+                part,
+                latest,
+                new,
+                plain,
+                pager: !no_pager,
+            };
+            handle_read(&storage, format, year, day, options).await
         }
         Commands::Submit {
             year,
             day,
             part,
             answer,
+            from_file,
+            from_command,
+            yes,
+            limits,
+            advance,
         } => {
-            handle_submit(year, day, part, &answer).await
+            let source = match (answer, from_file, from_command) {
+                (_, Some(path), _) => AnswerSource::File(path),
+                (_, _, Some(command)) => AnswerSource::Command(command, limits.limits()),
+                (Some(answer), _, _) if answer == "-" => AnswerSource::Stdin,
+                (answer, _, _) => AnswerSource::Literal(answer.unwrap_or_default()),
+            };
+            handle_submit(&storage, format, year, day, part, source, yes, advance).await
         }
+        Commands::Samples {
+            year,
+            day,
+            part,
+            picks,
+            list,
+            reset,
+        } => handle_samples(&storage, format, year, day, part, picks, list, reset),
+        Commands::Export { year, day, to, out } => {
+            handle_export(&storage, format, year, day, to, out.as_deref())
+        }
+        Commands::Archive { year, out, skip_assets } => {
+            handle_archive(&storage, format, year, &out, !skip_assets).await
+        }
+        Commands::Search {
+            query,
+            year,
+            fixed_strings,
+            case_sensitive,
+            limit,
+            open,
+        } => {
+            let pattern = if fixed_strings { regex::escape(&query) } else { query };
+            handle_search(&storage, format, &pattern, case_sensitive, year, limit, open).await
+        }
+        Commands::Status { year, day } => handle_status(&storage, format, year, day),
+        Commands::New {
+            year,
+            day,
+            lang,
+            template_dir,
+            cargo,
+            out,
+        } => {
+            let template = (!cargo).then_some((lang.as_str(), template_dir.as_deref()));
+            handle_new(&storage, format, year, day, template, &out)
+        }
+        Commands::Next { year, width } => handle_next(&storage, format, year, width).await,
+        Commands::Run {
+            year,
+            day,
+            part,
+            sample,
+            all,
+            bin,
+            limits,
+        } => handle_run(&storage, format, year, day, part, sample, all, bin, &limits.limits()),
+        Commands::Bench {
+            year,
+            day,
+            part,
+            runs,
+            warmup,
+            threshold,
+            command,
+            limits,
+        } => {
+            let solver = solver_from_args(command, limits.limits());
+            handle_bench(&storage, format, year, day, part, runs, warmup, threshold, solver)
+        }
+        Commands::Watch {
+            year,
+            day,
+            part,
+            sample,
+            patterns,
+            debounce,
+            command,
+            limits,
+        } => {
+            let solver = solver_from_args(command, limits.limits());
+            handle_watch(&storage, year, day, part, sample, patterns, debounce, solver)
+        }
+        Commands::Regress {
+            year,
+            command,
+            limits,
+        } => match require_solver(solver_from_args(command, limits.limits())) {
+            Ok(solver) => handle_regress(&storage, format, year, &solver),
+            Err(e) => Err(e),
+        },
     };
 
-    if let Err(e) = result {
-        error!("{e}");
-        std::process::exit(1);
+    // Report the writes a dry run skipped
+    let result = result.map(|mut data| {
+        if storage.is_dry_run() {
+            let planned = storage.planned_writes();
+            if format == OutputFormat::Text {
+                print!("{}", display::format_planned_writes(&planned));
+            }
+            data["planned_writes"] = serde_json::to_value(&planned).unwrap_or_default();
+        }
+        data
+    });
+
+    match result {
+        Ok(data) => {
+            if format == OutputFormat::Json {
+                print_json(&json!({ "ok": true, "command": command, "data": data }));
+            }
+
+            // Rejected answers and failing solutions are normal results,
+            // but scripts still need to see them
+            if data["result"]["correct"] == false {
+                std::process::exit(exit_code::WRONG_ANSWER);
+            }
+            if let Some(code) = data["exit_code"].as_i64().filter(|&c| c != 0) {
+                std::process::exit(code as i32);
+            }
+        }
+        Err(e) => {
+            error!("{e}");
+            if format == OutputFormat::Json {
+                print_json(&json!({
+                    "ok": false,
+                    "command": command,
+                    "error": {
+                        "code": e.code(),
+                        "exit_code": e.exit_code(),
+                        "message": e.to_string(),
+                    },
+                }));
+            }
+            std::process::exit(e.exit_code());
+        }
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Print a single JSON document on stdout
+fn print_json(value: &Value) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+}
+
 async fn handle_fetch(
-    base_path: Option<String>,
+    storage: &Storage,
     year: i32,
     day: i32,
     part: i32,
     description_only: bool,
     input_only: bool,
-    description_path: Option<String>,
-    input_path: Option<String>,
-    sample_path: Option<String>,
-    sample_answer_path: Option<String>,
-) -> error::Result<()> {
+    use_custom_paths: bool,
+) -> error::Result<Value> {
     let mut client = EcClient::new()?;
+    let mut written = Vec::new();
+    let mut unpaired = Vec::new();
 
-    // Build storage with custom paths
-    let mut storage = Storage::new(base_path.map(|p| p.into()));
-
-    // Track if custom sample/answer paths are used
-    let use_custom_paths = sample_path.is_some() || sample_answer_path.is_some();
-
-    if let Some(desc_path) = description_path {
-        storage = storage.with_description_path(desc_path.into());
-    }
-
-    if let Some(inp_path) = input_path {
-        storage = storage.with_input_path(inp_path.into());
-    }
-
-    if let Some(samp_path) = sample_path {
-        storage = storage.with_sample_path(samp_path.into());
-    }
-
-    if let Some(samp_ans_path) = sample_answer_path {
-        storage = storage.with_sample_answer_path(samp_ans_path.into());
-    }
+    // Cache keys so status and read know which parts are unlocked
+    let keys = client.fetch_quest_keys(year, day).await?;
+    written.push(storage.save_keys(year, day, &keys)?);
 
     // Fetch description (unless input_only)
     if !input_only {
         let description = client.fetch_description(year, day).await?;
         let path = storage.save_description(year, day, &description)?;
         info!("Description saved to {path:?}");
+        written.push(path);
 
-        // Extract and save samples with expected answers
-        // Split description by parts to extract samples and answers per part
-        // The separator pattern added by fetch_description is: "\n PART N \n" surrounded by "=" lines
-        let part2_separator = " PART 2 ";
-        let part3_separator = " PART 3 ";
-
-        let parts: Vec<&str> = description.split(part2_separator).collect();
-        let (part1_html, rest) = if parts.len() > 1 {
-            (parts[0], parts[1])
-        } else {
-            (description.as_str(), "")
-        };
-
-        let (part2_html, part3_html) = if !rest.is_empty() {
-            let parts3: Vec<&str> = rest.split(part3_separator).collect();
-            if parts3.len() > 1 {
-                (parts3[0], parts3[1])
-            } else {
-                (rest, "")
-            }
-        } else {
-            ("", "")
-        };
-
-        // Extract last sample and expected answer for each part
         // When custom sample/answer paths are specified, only save for the requested part
         // to avoid overwriting files. Otherwise, save for all available parts.
+        let only_part = use_custom_paths.then_some(part);
+        written.extend(storage.save_samples(year, day, &description, only_part)?);
+        unpaired = storage
+            .description_samples(year, day, &description)?
+            .into_iter()
+            .filter(|s| s.expected_answer.is_none() && only_part.is_none_or(|p| p == s.part))
+            .collect();
 
-        for (part_num, part_html) in [(1, part1_html), (2, part2_html), (3, part3_html)]
-            .iter()
-            .filter(|(_, html)| !html.is_empty())
-            .filter(|(pnum, _)| !use_custom_paths || *pnum == part)
-        {
-            let samples = display::extract_samples(part_html);
-            let expected_answer = display::extract_expected_answer(part_html);
-
-            if let Some(sample) = samples.last() {
-                let path = storage.save_sample(year, day, *part_num, sample)?;
-                info!("Sample for part {part_num} saved to {path:?}");
-
-                if let Some(answer) = expected_answer {
-                    let answer_path =
-                        storage.save_expected_answer(year, day, *part_num, &answer)?;
-                    info!("Expected answer for part {part_num} saved to {answer_path:?}");
-                } else {
-                    warn!("Could not extract expected answer for part {part_num}");
-                }
-            }
-        }
+        // Keep tests generated by `new --cargo` in step with newly unlocked parts
+        written.extend(scaffold::regenerate_sample_tests(storage, year, day)?);
     }
 
     // Fetch input (unless description_only)
@@ -178,15 +307,37 @@ async fn handle_fetch(
         let input = client.fetch_input(year, day, part).await?;
         let path = storage.save_input(year, day, part, &input)?;
         info!("Input saved to {path:?}");
+        written.push(path);
     }
 
-    Ok(())
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "part": part,
+        "files": written,
+        "unpaired_samples": unpaired,
+    }))
 }
 
-async fn handle_read(base_path: Option<String>, year: i32, day: i32, width: Option<usize>) -> error::Result<()> {
-    let storage = Storage::new(base_path.map(|p| p.into()));
+/// How `read` should present a description
+struct ReadOptions {
+    width: Option<usize>,
+    part: Option<i32>,
+    latest: bool,
+    new: bool,
+    plain: bool,
+    pager: bool,
+}
 
+async fn handle_read(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    options: ReadOptions,
+) -> error::Result<Value> {
     // Check if description exists locally and if it needs updating
+    let mut replaced = None;
     let description = if storage.has_description(year, day) {
         let cached = storage.load_description(year, day)?;
 
@@ -194,17 +345,19 @@ async fn handle_read(base_path: Option<String>, year: i32, day: i32, width: Opti
         let client = EcClient::new()?;
         let keys = client.fetch_quest_keys(year, day).await?;
 
+        storage.save_keys(year, day, &keys)?;
+
         // Count how many parts we have keys for
-        let available_parts = 1 + keys.key2.is_some() as usize + keys.key3.is_some() as usize;
+        let available_parts = keys.unlocked_parts() as usize;
 
-        // Count how many PART markers are in the cached description
-        // Part 1 has no marker, so parts 2 and 3 add markers
-        let cached_parts = 1 + cached.matches(" PART 2 ").count() + cached.matches(" PART 3 ").count();
+        // Count how many parts are in the cached description
+        let cached_parts = display::split_parts(&cached).len();
 
         if cached_parts < available_parts {
             info!("New parts unlocked, re-fetching description...");
             let desc = client.fetch_description(year, day).await?;
             storage.save_description(year, day, &desc)?;
+            replaced = Some(cached);
             desc
         } else {
             info!("Reading description from local storage...");
@@ -219,26 +372,814 @@ async fn handle_read(base_path: Option<String>, year: i32, day: i32, width: Opti
     };
 
     // Determine terminal width
-    let display_width = width.unwrap_or_else(|| {
+    let display_width = options.width.unwrap_or_else(|| {
         term_size::dimensions()
             .map(|(w, _)| w)
             .unwrap_or(80)
     });
 
+    // Only the sections added since the previous version, when asked
+    let new_sections = if options.new {
+        match storage.previous_description(year, day)? {
+            Some(previous) => Some(display::added_sections(&previous, &description)),
+            None => {
+                info!("No earlier version of {year}/{day} is stored; showing the whole description");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Point out what a re-fetch brought in
+    let new_parts: Vec<i32> = match (&new_sections, &replaced) {
+        (Some(sections), _) => sections.iter().map(|(part, _)| *part).collect(),
+        (None, Some(previous)) => display::added_sections(previous, &description)
+            .into_iter()
+            .map(|(part, _)| part)
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+    let notice = (replaced.is_some() && !options.new && !new_parts.is_empty()).then(|| {
+        let parts = new_parts.iter().map(i32::to_string).collect::<Vec<_>>().join(", ");
+        format!(
+            "New since the last stored version: part {parts}. \
+             Run `ec-cli read -y {year} -d {day} --new` to see only the new text.\n\n"
+        )
+    });
+
+    // Pick the parts to show
+    let all_parts: Vec<(i32, &str)> = (1..).zip(display::split_parts(&description)).collect();
+    let parts = match (&new_sections, options.part, options.latest) {
+        (Some(sections), _, _) => sections.iter().map(|(part, html)| (*part, html.as_str())).collect(),
+        (None, Some(part), _) => {
+            let selected: Vec<_> = all_parts.iter().copied().filter(|(p, _)| *p == part).collect();
+            if selected.is_empty() {
+                return Err(EcError::QuestNotAvailable {
+                    year,
+                    day,
+                    part: Some(part),
+                });
+            }
+            selected
+        }
+        (None, None, true) => all_parts.last().copied().into_iter().collect(),
+        (None, None, false) => all_parts.clone(),
+    };
+    let filtered = new_sections.is_some() || parts.len() != all_parts.len();
+
     // Convert HTML to text and display
-    let text = display::html_to_text(&description, display_width);
-    println!("{text}");
+    let text = if filtered {
+        parts
+            .iter()
+            .map(|(_, html)| display::html_to_text(html, display_width))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        display::html_to_text(&description, display_width)
+    };
+    if format == OutputFormat::Text {
+        let output = if parts.is_empty() {
+            "Nothing new since the last stored version\n".to_string()
+        } else if options.plain {
+            format!("{text}\n")
+        } else {
+            let color = render::use_color(std::io::stdout().is_terminal());
+            if filtered {
+                render::render_parts(&parts, display_width, color)
+            } else {
+                render::render_description(&description, display_width, color)
+            }
+        };
+        pager::page(&(notice.unwrap_or_default() + &output), options.pager)?;
+    }
 
-    Ok(())
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "title": display::extract_title(&description),
+        "description_path": storage.description_file(year, day),
+        "parts": all_parts.len(),
+        "shown_parts": parts.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+        "new_parts": new_parts,
+        "text": text,
+    }))
 }
 
-async fn handle_submit(year: i32, day: i32, part: i32, answer: &str) -> error::Result<()> {
-    let client = EcClient::new()?;
-    let response = client.submit_answer(year, day, part, answer).await?;
+#[allow(clippy::too_many_arguments)]
+async fn handle_submit(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    part: i32,
+    source: AnswerSource,
+    yes: bool,
+    advance: bool,
+) -> error::Result<Value> {
+    let answer = answer::normalize_answer(&source.read()?)?;
+    info!("Answer to submit: {answer}");
+
+    let mut client = EcClient::new()?;
+
+    // Show the exact request instead of sending it
+    if storage.is_dry_run() {
+        let request = client.submit_request_preview(year, day, part, &answer);
+        if format == OutputFormat::Text {
+            println!("Dry run: would send\n\n{}", display::format_request_preview(&request));
+        }
+        return Ok(json!({ "year": year, "day": day, "part": part, "request": request }));
+    }
+
+    if !yes {
+        // stdin already held the answer, so there's nobody left to ask
+        if source.uses_stdin() {
+            return Err(EcError::InvalidAnswer(
+                "use --yes when reading the answer from stdin".to_string(),
+            ));
+        }
+        if !answer::confirm(&format!("Submit \"{answer}\" for {year}/{day} part {part}?"))? {
+            return Err(EcError::Aborted);
+        }
+    }
+
+    let response = client.submit_answer(year, day, part, &answer).await?;
+
+    // Record the outcome in the local history
+    let submission = Submission {
+        part,
+        answer: answer.to_string(),
+        correct: response.correct,
+        length_correct: response.length_correct,
+        first_correct: response.first_correct,
+        global_place: response.global_place,
+        submitted_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    };
+    let path = storage.record_submission(year, day, &submission)?;
+    debug!("Submission recorded in {path:?}");
 
     // Display formatted response
-    let output = display::format_submit_response(&response);
-    println!("{output}");
+    if format == OutputFormat::Text {
+        let output = display::format_submit_response(&response);
+        println!("{output}");
+    }
+
+    // Prepare the newly unlocked part straight away
+    //
+    // The answer is already accepted and recorded, so a failure here is only
+    // reported; it must not turn a correct submission into an error.
+    let mut advanced = Value::Null;
+    if advance && response.correct && part < 3 {
+        let result = async {
+            let keys = client.fetch_quest_keys(year, day).await?;
+            storage.save_keys(year, day, &keys)?;
+
+            if keys.is_unlocked(part + 1) {
+                prepare_part(&mut client, storage, format, year, day, part + 1, None).await
+            } else {
+                warn!("Part {} is not unlocked yet", part + 1);
+                Ok(Value::Null)
+            }
+        }
+        .await;
+
+        advanced = result.unwrap_or_else(|e| {
+            warn!("Could not prepare part {}: {e}", part + 1);
+            json!({ "error": e.to_string() })
+        });
+    }
+
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "part": part,
+        "answer": answer,
+        "result": response,
+        "advanced": advanced,
+    }))
+}
+
+async fn handle_next(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    width: Option<usize>,
+) -> error::Result<Value> {
+    let mut client = EcClient::new()?;
+
+    let Some((day, part)) = next::find(&mut client, storage, year).await? else {
+        if format == OutputFormat::Text {
+            println!("No unsolved parts available for {year}");
+        }
+        return Ok(Value::Null);
+    };
+
+    prepare_part(&mut client, storage, format, year, day, part, width).await
+}
+
+/// Fetch a part's input and refreshed description, then print its paths and section
+async fn prepare_part(
+    client: &mut EcClient,
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    part: i32,
+    width: Option<usize>,
+) -> error::Result<Value> {
+    let prepared = next::prepare(client, storage, year, day, part).await?;
 
-    Ok(())
+    let display_width = width.unwrap_or_else(|| {
+        term_size::dimensions()
+            .map(|(w, _)| w)
+            .unwrap_or(80)
+    });
+
+    let section = display::split_parts(&prepared.description)
+        .get(part as usize - 1)
+        .map(|html| display::html_to_text(html, display_width))
+        .unwrap_or_default();
+
+    if format == OutputFormat::Text {
+        println!("Quest {year}/{day} part {part}");
+        println!("  Description: {}", prepared.description_path.display());
+        println!("  Input:       {}", prepared.input_path.display());
+        for sample in &prepared.samples {
+            let answer = sample.expected_answer.as_deref().unwrap_or("(no expected answer)");
+            println!("  Sample {}:    {}  → {answer}", sample.number, sample.path.display());
+        }
+        println!();
+        println!("{section}");
+    }
+
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "part": part,
+        "description_path": prepared.description_path,
+        "input_path": prepared.input_path,
+        "samples": prepared.samples,
+        "section": section,
+        "files": prepared.files,
+    }))
+}
+
+fn handle_new(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    template: Option<(&str, Option<&std::path::Path>)>,
+    out: &std::path::Path,
+) -> error::Result<Value> {
+    let context = scaffold::TemplateContext::from_storage(storage, year, day)?;
+
+    // Without a template, generate a Cargo workspace member
+    let (results, member) = match template {
+        Some((lang, template_dir)) => {
+            let files = scaffold::load_template(lang, template_dir)?;
+            (scaffold::scaffold(storage, &files, &context, out)?, None)
+        }
+        None => {
+            let (member, results) = scaffold::scaffold_cargo(storage, &context, year, day, out)?;
+            (results, Some(member))
+        }
+    };
+
+    if format == OutputFormat::Text {
+        for (path, action) in &results {
+            match action {
+                ScaffoldAction::Created => println!("created {}", path.display()),
+                ScaffoldAction::Updated => println!("updated {}", path.display()),
+                ScaffoldAction::Skipped => println!("exists  {} (skipped)", path.display()),
+            }
+        }
+        println!("{} sample test(s) generated", context.samples().len());
+        if let Some(member) = &member {
+            println!("Run them with: cargo test -p {}", member.package);
+        }
+    }
+
+    let files: Vec<Value> = results
+        .iter()
+        .map(|(path, action)| json!({ "path": path, "action": action }))
+        .collect();
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "lang": template.map_or("cargo", |(lang, _)| lang),
+        "member": member,
+        "files": files,
+        "sample_tests": context.samples().len(),
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_samples(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    part: i32,
+    mut picks: Vec<SamplePick>,
+    list: bool,
+    reset: bool,
+) -> error::Result<Value> {
+    if !storage.has_description(year, day) {
+        return Err(EcError::MissingData {
+            what: "description (run fetch first)".to_string(),
+            path: storage.description_file(year, day),
+        });
+    }
+    let description = storage.load_description(year, day)?;
+    let part_html = *display::split_parts(&description)
+        .get(part as usize - 1)
+        .ok_or_else(|| {
+            EcError::InvalidArgument(format!(
+                "part {part} isn't in the stored description; fetch it again once it unlocks"
+            ))
+        })?;
+
+    let blocks = extract::pre_blocks(part_html);
+    let mut metadata = storage.load_metadata(year, day)?;
+    let interactive = picks.is_empty()
+        && !list
+        && !reset
+        && format == OutputFormat::Text
+        && std::io::stdin().is_terminal();
+
+    if format == OutputFormat::Text {
+        println!("Quest {year}/{day} part {part}: {} <pre> block(s)\n", blocks.len());
+        print!("{}", display::format_pre_blocks(&blocks));
+        if let Some(current) = metadata.sample_picks.get(&part) {
+            println!("Current picks: {}", format_picks(current));
+        }
+    }
+
+    if interactive {
+        println!("Pick each sample as INPUT[+INPUT][:ANSWER] (e.g. 1:2 or 1+2:3); empty line to finish.");
+        loop {
+            let reply = answer::prompt(&format!("Sample {}:", picks.len() + 1))?;
+            if reply.is_empty() {
+                break;
+            }
+            match reply.parse::<SamplePick>() {
+                Ok(pick) => picks.push(pick),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+
+    let samples = if reset {
+        metadata.sample_picks.remove(&part);
+        extract::extract_part_samples(part, part_html)
+    } else if picks.is_empty() {
+        // Nothing chosen: listing only
+        return Ok(json!({ "year": year, "day": day, "part": part, "blocks": blocks }));
+    } else {
+        let samples = extract::apply_picks(part, part_html, &picks)?;
+        metadata.sample_picks.insert(part, picks.clone());
+        samples
+    };
+
+    let mut written = vec![storage.save_metadata(year, day, &metadata)?];
+    written.extend(storage.save_part_samples(year, day, part, &samples)?);
+    written.extend(scaffold::regenerate_sample_tests(storage, year, day)?);
+
+    if format == OutputFormat::Text {
+        if reset {
+            println!("Picks forgotten; {} sample(s) extracted automatically", samples.len());
+        } else {
+            println!("Saved {} sample(s); later fetches will keep them", samples.len());
+        }
+    }
+
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "part": part,
+        "blocks": blocks,
+        "picks": metadata.sample_picks.get(&part),
+        "samples": samples,
+        "files": written,
+    }))
+}
+
+/// Render picks back in the `--pick` syntax
+fn format_picks(picks: &[SamplePick]) -> String {
+    picks
+        .iter()
+        .map(|pick| {
+            let inputs: Vec<String> = pick.inputs.iter().map(|n| n.to_string()).collect();
+            match pick.answer {
+                Some(answer) => format!("{}:{answer}", inputs.join("+")),
+                None => inputs.join("+"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn handle_export(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: Option<i32>,
+    to: ExportFormat,
+    out: Option<&std::path::Path>,
+) -> error::Result<Value> {
+    let days = match day {
+        Some(day) => {
+            if !storage.has_description(year, day) {
+                return Err(EcError::MissingData {
+                    what: "description (run fetch first)".to_string(),
+                    path: storage.description_file(year, day),
+                });
+            }
+            vec![day]
+        }
+        None => storage
+            .quests_with_descriptions()?
+            .into_iter()
+            .filter_map(|(y, day)| (y == year).then_some(day))
+            .collect(),
+    };
+
+    let document = |day: i32| -> error::Result<String> {
+        let description = storage.load_description(year, day)?;
+        let unlocked = storage.load_keys(year, day)?.map(|k| k.unlocked_parts());
+        Ok(match to {
+            ExportFormat::Md => markdown::quest_markdown(year, day, &description, unlocked),
+        })
+    };
+
+    // A single quest without a destination goes to stdout
+    let Some(out) = out else {
+        let Some(day) = day else {
+            return Err(EcError::InvalidArgument(
+                "exporting a whole year needs --out DIR".to_string(),
+            ));
+        };
+        let document = document(day)?;
+        if format == OutputFormat::Text {
+            print!("{document}");
+        }
+        return Ok(json!({ "year": year, "day": day, "document": document }));
+    };
+
+    let mut files = Vec::new();
+    for day in days {
+        let path = out.join(year.to_string()).join(format!("q{day:02}.md"));
+        storage.save_file(&path, &document(day)?)?;
+        if format == OutputFormat::Text {
+            println!("Exported {year}/{day} to {}", path.display());
+        }
+        files.push(json!({ "day": day, "path": path }));
+    }
+    if files.is_empty() && format == OutputFormat::Text {
+        println!("No stored descriptions for {year}");
+    }
+
+    Ok(json!({ "year": year, "files": files }))
+}
+
+async fn handle_archive(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    out: &std::path::Path,
+    download: bool,
+) -> error::Result<Value> {
+    let archive = archive::build(storage, year, out, download).await?;
+
+    if format == OutputFormat::Text {
+        println!(
+            "Archived {} quest(s) of {year} to {} ({} asset(s) stored locally)",
+            archive.pages.len(),
+            archive.index.display(),
+            archive.assets
+        );
+        if !archive.failed_assets.is_empty() {
+            println!(
+                "{} asset(s) could not be downloaded and still point at the live site",
+                archive.failed_assets.len()
+            );
+        }
+    }
+
+    Ok(json!({
+        "year": year,
+        "index": archive.index,
+        "pages": archive.pages,
+        "assets": archive.assets,
+        "failed_assets": archive.failed_assets,
+    }))
+}
+
+async fn handle_search(
+    storage: &Storage,
+    format: OutputFormat,
+    pattern: &str,
+    case_sensitive: bool,
+    year: Option<i32>,
+    limit: usize,
+    open: bool,
+) -> error::Result<Value> {
+    let pattern = regex::RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| EcError::InvalidArgument(format!("invalid search pattern: {e}")))?;
+
+    let mut hits = Vec::new();
+    for (y, day) in storage.quests_with_descriptions()? {
+        if year.is_some_and(|year| year != y) {
+            continue;
+        }
+        let description = storage.load_description(y, day)?;
+        hits.extend(search::search_description(&pattern, y, day, &description));
+    }
+    search::rank(&mut hits);
+    let total = hits.len();
+    hits.truncate(limit);
+
+    if open {
+        let Some(best) = hits.first() else {
+            return Err(EcError::InvalidArgument(format!("nothing matches {:?}", pattern.as_str())));
+        };
+        info!("Opening {}/{} part {}", best.year, best.day, best.part);
+        let options = ReadOptions {
+            width: None,
+            part: Some(best.part),
+            latest: false,
+            new: false,
+            plain: false,
+            pager: true,
+        };
+        return handle_read(storage, format, best.year, best.day, options).await;
+    }
+
+    if format == OutputFormat::Text {
+        if hits.is_empty() {
+            println!("No matches for {:?}", pattern.as_str());
+        } else {
+            let color = render::use_color(std::io::stdout().is_terminal());
+            print!("{}", display::format_search_hits(&hits, color));
+            if total > hits.len() {
+                println!("… {} more (raise --limit to see them)", total - hits.len());
+            }
+        }
+    }
+
+    Ok(json!({
+        "query": pattern.as_str(),
+        "total": total,
+        "hits": hits,
+    }))
+}
+
+fn handle_status(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+) -> error::Result<Value> {
+    let keys = storage.load_keys(year, day)?;
+    let submissions = storage.load_submissions(year, day)?;
+
+    let (title, description_path) = if storage.has_description(year, day) {
+        let description = storage.load_description(year, day)?;
+        (
+            display::extract_title(&description),
+            Some(storage.description_file(year, day)),
+        )
+    } else {
+        (None, None)
+    };
+
+    let parts = (1..=3)
+        .map(|part| -> error::Result<PartStatus> {
+            Ok(PartStatus {
+                part,
+                unlocked: keys.as_ref().map(|k| k.is_unlocked(part)),
+                input_path: storage
+                    .has_input(year, day, part)
+                    .then(|| storage.input_file(year, day, part)),
+                samples: storage.stored_samples(year, day, part)?,
+                submissions: submissions.iter().filter(|s| s.part == part).cloned().collect(),
+            })
+        })
+        .collect::<error::Result<Vec<_>>>()?;
+
+    let status = QuestStatus {
+        year,
+        day,
+        title,
+        description_path,
+        parts,
+    };
+
+    if format == OutputFormat::Text {
+        print!("{}", display::format_status(&status));
+    }
+
+    Ok(serde_json::to_value(&status)?)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_run(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: Option<i32>,
+    part: Option<i32>,
+    sample: Option<usize>,
+    all: bool,
+    bin: Option<String>,
+    limits: &SolverLimits,
+) -> error::Result<Value> {
+    let cargo = |subcommand: &str| {
+        let mut command = std::process::Command::new("cargo");
+        command.args([subcommand, "--release", "--quiet"]);
+        if let Some(bin) = &bin {
+            command.args(["--bin", bin]);
+        }
+        command
+    };
+
+    // Build first so compilation doesn't count against the limits
+    let build = cargo("build").status()?;
+    if !build.success() {
+        return Err(EcError::SolverFailed {
+            reason: format!("failed to build ({build})"),
+        });
+    }
+
+    // The solutions binary hands its registry to `ec_cli::Harness::main`
+    let mut command = cargo("run");
+    command.arg("--");
+    if all {
+        command.arg("--all");
+    } else {
+        command.args(["--year", &year.to_string()]);
+    }
+    if let Some(day) = day {
+        command.args(["--day", &day.to_string()]);
+    }
+    if let Some(part) = part {
+        command.args(["--part", &part.to_string()]);
+    }
+    if let Some(sample) = sample {
+        command.args(["--sample", &sample.to_string()]);
+    }
+
+    let base_path = std::path::absolute(storage.base_path())?;
+    command.env(ec_cli::inputs::BASE_PATH_ENV, &base_path);
+    if format == OutputFormat::Json {
+        // Captured so a crash can be reported instead of unparseable output
+        command.env("EC_FORMAT", "json").stderr(std::process::Stdio::piped());
+    }
+    command.stdin(std::process::Stdio::null());
+    debug!("Running solution harness: {command:?}");
+
+    let output = solver::run_limited(command, limits)?;
+    let exit = match output.status {
+        SolverStatus::Success => 0,
+        SolverStatus::Failed(code) => code.unwrap_or(exit_code::FAILURE),
+        SolverStatus::TimedOut => {
+            return Err(EcError::SolverTimedOut {
+                timeout: limits.timeout.unwrap_or_default(),
+            })
+        }
+        SolverStatus::OutputLimitExceeded => {
+            return Err(EcError::SolverFailed {
+                reason: output.status.to_string(),
+            })
+        }
+    };
+
+    if format == OutputFormat::Text {
+        print!("{}", output.stdout);
+        return Ok(json!({ "exit_code": exit }));
+    }
+
+    // The harness prints its report and exits non-zero when an answer is
+    // wrong; anything else that exits non-zero crashed before reporting
+    let reports: Value = match serde_json::from_str(&output.stdout) {
+        Ok(reports) => reports,
+        Err(_) if exit != 0 => {
+            return Err(EcError::SolverFailed {
+                reason: format!("harness exited with code {exit} without a report: {}", output.stderr.trim()),
+            })
+        }
+        Err(e) => return Err(e.into()),
+    };
+    eprint!("{}", output.stderr);
+    Ok(json!({ "reports": reports, "exit_code": exit }))
+}
+
+fn handle_regress(
+    storage: &Storage,
+    format: OutputFormat,
+    year: Option<i32>,
+    solver: &SolverCommand,
+) -> error::Result<Value> {
+    let results = regress::run(storage, year, solver)?;
+
+    if format == OutputFormat::Text {
+        print!("{}", display::format_regress_matrix(&results));
+    }
+
+    let exit = if regress::regressed(&results) { exit_code::WRONG_ANSWER } else { 0 };
+    Ok(json!({
+        "results": results,
+        "exit_code": exit,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_bench(
+    storage: &Storage,
+    format: OutputFormat,
+    year: i32,
+    day: i32,
+    part: i32,
+    runs: usize,
+    warmup: usize,
+    threshold: f64,
+    solver: Option<SolverCommand>,
+) -> error::Result<Value> {
+    let solver = require_solver(solver)?;
+    let report = bench::run(storage, &solver, year, day, part, runs, warmup, threshold)?;
+    let stats = &report.record.stats;
+
+    if format == OutputFormat::Text {
+        print!("{}", display::format_bench(stats, report.comparison.as_ref()));
+    }
+
+    Ok(json!({
+        "year": year,
+        "day": day,
+        "part": part,
+        "command": report.record.command,
+        "stats": stats,
+        "comparison": report.comparison,
+    }))
+}
+
+/// Solver command given after `--`, falling back to `EC_SOLVER`
+fn solver_from_args(command: Vec<String>, limits: SolverLimits) -> Option<SolverCommand> {
+    let solver = if command.is_empty() {
+        SolverCommand::from_env()
+    } else {
+        Some(SolverCommand::new(command.join(" ")))
+    };
+    solver.map(|s| s.with_limits(limits))
+}
+
+/// Solver given after `--`, falling back to `EC_SOLVER`
+fn require_solver(solver: Option<SolverCommand>) -> error::Result<SolverCommand> {
+    solver.ok_or_else(|| {
+        EcError::InvalidArgument(format!(
+            "no solver command given (pass it after -- or set {})",
+            ec_cli::solver::SOLVER_ENV
+        ))
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_watch(
+    storage: &Storage,
+    year: i32,
+    day: i32,
+    part: i32,
+    sample: Option<usize>,
+    patterns: Vec<String>,
+    debounce: u64,
+    solver: Option<SolverCommand>,
+) -> error::Result<Value> {
+    let solver = require_solver(solver)?;
+    let mut watcher = FileWatcher::new(patterns.clone());
+    info!("Watching {} file(s) matching {patterns:?}", watcher.file_count());
+
+    let poll = std::time::Duration::from_millis(250);
+    let debounce = std::time::Duration::from_millis(debounce);
+    let mut changed: Vec<std::path::PathBuf> = Vec::new();
+
+    loop {
+        // Clear the screen and move the cursor home
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "ec-cli watch {year}/{day} part {part}  ({})",
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        for path in &changed {
+            println!("  changed: {}", path.display());
+        }
+        println!();
+
+        watch::check_round(storage, &solver, year, day, part, sample, |check| println!("{check}"))?;
+
+        println!("\nWaiting for changes... (Ctrl+C to stop)");
+        changed = watcher.wait_for_change(poll, debounce);
+    }
 }
@@ -0,0 +1,331 @@
+//! CommonMark export of quest descriptions
+//!
+//! Converts the stored description HTML into Markdown for notes: headings,
+//! paragraphs, `**bold**`/`*italic*`, inline code, links, lists and fenced
+//! code blocks for `<pre>`. Each part becomes a `## Part N` section, and the
+//! file opens with YAML front-matter describing the quest.
+
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+use crate::display;
+
+/// A full Markdown document for a quest: front-matter plus one section per part
+///
+/// `unlocked_parts` comes from the stored quest keys; without them the number
+/// of parts in the description is used.
+pub fn quest_markdown(year: i32, day: i32, description: &str, unlocked_parts: Option<i32>) -> String {
+    let parts = display::split_parts(description);
+    let title = display::extract_title(description).unwrap_or_else(|| format!("Quest {day}"));
+
+    let mut out = String::from("---\n");
+    // A JSON string is a valid YAML scalar, and escapes quotes and colons
+    out.push_str(&format!("title: {}\n", serde_json::Value::from(title)));
+    out.push_str(&format!("year: {year}\n"));
+    out.push_str(&format!("day: {day}\n"));
+    out.push_str(&format!(
+        "unlocked_parts: {}\n",
+        unlocked_parts.unwrap_or(parts.len() as i32)
+    ));
+    out.push_str("---\n");
+
+    for (part, html) in (1..).zip(parts) {
+        out.push_str(&format!("\n## Part {part}\n\n"));
+        // Nest the description's own headings under the part heading
+        out.push_str(&convert(html, 1));
+    }
+
+    out
+}
+
+/// Convert an HTML fragment into CommonMark
+pub fn html_to_markdown(html: &str) -> String {
+    convert(html, 0)
+}
+
+/// Convert with every heading moved `shift` levels down
+fn convert(html: &str, shift: usize) -> String {
+    let document = Html::parse_fragment(html);
+    let blocks = blocks(document.tree.root(), shift);
+
+    if blocks.is_empty() {
+        String::new()
+    } else {
+        blocks.join("\n\n") + "\n"
+    }
+}
+
+/// Block-level Markdown for a node's children, one string per block
+fn blocks(node: NodeRef<Node>, shift: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut paragraph = String::new();
+
+    for child in node.children() {
+        let Node::Element(element) = child.value() else {
+            paragraph.push_str(&inline(child));
+            continue;
+        };
+
+        let block = match element.name() {
+            "script" | "style" => continue,
+            name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let level = (name[1..].parse::<usize>().unwrap() + shift).min(6);
+                let text = clean(&inline_children(child)).replace('\n', " ");
+                (!text.is_empty()).then(|| format!("{} {text}", "#".repeat(level)))
+            }
+            "pre" => Some(fence(&child.children().map(pre_text).collect::<String>())),
+            "ul" | "ol" => {
+                let ordered = element.name() == "ol";
+                let items: Vec<String> = child
+                    .children()
+                    .filter(|li| matches!(li.value(), Node::Element(e) if e.name() == "li"))
+                    .enumerate()
+                    .map(|(i, li)| {
+                        let marker = if ordered { format!("{}. ", i + 1) } else { "- ".to_string() };
+                        indent(&blocks(li, shift).join("\n\n"), &marker, &" ".repeat(marker.len()))
+                    })
+                    .collect();
+                (!items.is_empty()).then(|| items.join("\n"))
+            }
+            "blockquote" => {
+                let inner = blocks(child, shift).join("\n\n");
+                (!inner.is_empty()).then(|| indent(&inner, "> ", "> "))
+            }
+            "hr" => Some("---".to_string()),
+            "html" | "body" | "p" | "div" | "section" | "article" | "table" | "tr" => {
+                let inner = blocks(child, shift);
+                (!inner.is_empty()).then(|| inner.join("\n\n"))
+            }
+            _ => {
+                paragraph.push_str(&inline(child));
+                continue;
+            }
+        };
+
+        flush(&mut paragraph, &mut out);
+        out.extend(block);
+    }
+    flush(&mut paragraph, &mut out);
+
+    out
+}
+
+fn flush(paragraph: &mut String, blocks: &mut Vec<String>) {
+    let text = clean(&std::mem::take(paragraph));
+    if !text.is_empty() {
+        blocks.push(text);
+    }
+}
+
+/// Inline Markdown for a node
+fn inline(node: NodeRef<Node>) -> String {
+    match node.value() {
+        Node::Text(text) => escape(&collapse(text)),
+        Node::Element(element) => match element.name() {
+            "br" => "\\\n".to_string(),
+            "b" | "strong" => emphasis(&inline_children(node), "**"),
+            "em" | "i" => emphasis(&inline_children(node), "*"),
+            "code" => code_span(&node.descendants().filter_map(text_of).collect::<String>()),
+            "a" => {
+                let text = inline_children(node);
+                match element.attr("href") {
+                    Some(href) if !text.trim().is_empty() => format!("[{}]({href})", text.trim()),
+                    _ => text,
+                }
+            }
+            "img" => format!(
+                "![{}]({})",
+                element.attr("alt").unwrap_or_default(),
+                element.attr("src").unwrap_or_default()
+            ),
+            "script" | "style" => String::new(),
+            _ => inline_children(node),
+        },
+        _ => String::new(),
+    }
+}
+
+fn inline_children(node: NodeRef<Node>) -> String {
+    node.children().map(inline).collect()
+}
+
+fn text_of(node: NodeRef<'_, Node>) -> Option<&str> {
+    match node.value() {
+        Node::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// Text inside `<pre>`, verbatim; `<br>` counts as a line break
+fn pre_text(node: NodeRef<Node>) -> String {
+    match node.value() {
+        Node::Text(text) => text.to_string(),
+        Node::Element(element) if element.name() == "br" => "\n".to_string(),
+        _ => node.children().map(pre_text).collect(),
+    }
+}
+
+/// Wrap text in emphasis markers, keeping surrounding spaces outside them
+fn emphasis(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = if text.starts_with(' ') { " " } else { "" };
+    let trailing = if text.ends_with(' ') { " " } else { "" };
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+/// A code span whose delimiter is longer than any backtick run inside it
+fn code_span(code: &str) -> String {
+    let code = collapse(code);
+    let ticks = "`".repeat(longest_run(&code, '`') + 1);
+    let pad = if code.starts_with('`') || code.ends_with('`') { " " } else { "" };
+    format!("{ticks}{pad}{code}{pad}{ticks}")
+}
+
+/// A fenced code block, verbatim, with blank edge lines dropped
+fn fence(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(start, |i| i + 1);
+    let body = lines[start..end].join("\n");
+
+    let ticks = "`".repeat(longest_run(&body, '`').max(2) + 1);
+    if body.is_empty() {
+        format!("{ticks}\n{ticks}")
+    } else {
+        format!("{ticks}\n{body}\n{ticks}")
+    }
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|ch| ch != c).map(str::len).max().unwrap_or(0)
+}
+
+/// Prefix the first line with `first` and the others with `rest`
+fn indent(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !space {
+                out.push(' ');
+            }
+            space = true;
+        } else {
+            out.push(c);
+            space = false;
+        }
+    }
+    out
+}
+
+/// Tidy a paragraph: merge doubled spaces and trim every line
+fn clean(text: &str) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let mut line = line.trim().to_string();
+            while line.contains("  ") {
+                line = line.replace("  ", " ");
+            }
+            line
+        })
+        .collect();
+    let text = lines.join("\n");
+    let text = text.trim_end_matches("\\\n").trim_end_matches('\\');
+    escape_line_starts(text.trim())
+}
+
+/// Escape characters that Markdown would otherwise treat as markup
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape line starts that would turn a paragraph into a heading, list or quote
+fn escape_line_starts(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let digits = line.chars().take_while(char::is_ascii_digit).count();
+            let rest = &line[digits..];
+            if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+                format!("{}\\{}", &line[..digits], rest)
+            } else if line.starts_with(['#', '>', '+', '-', '='])
+                && line[1..].chars().next().is_none_or(|c| c == ' ' || c == line.as_bytes()[0] as char)
+            {
+                format!("\\{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASIC: &str = include_str!("../tests/fixtures/descriptions/basic.html");
+
+    #[test]
+    fn test_inline_markup() {
+        let html = r#"<h2 id="title">Quest 1: <em>Tiny</em> Potions</h2>
+<p>The answer is <b>42</b>, not <code>4*1</code>; see <a href="/2024/1">here</a>.</p>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "## Quest 1: *Tiny* Potions\n\nThe answer is **42**, not `4*1`; see [here](/2024/1).\n"
+        );
+    }
+
+    #[test]
+    fn test_pre_becomes_fence_and_lists() {
+        let html = "<p>Notes:</p><pre class=\"note\">\n#.#\n  x_y\n</pre><ul><li>one <b>1</b></li><li><p>two</p></li></ul><ol><li>first</li></ol>";
+        assert_eq!(
+            html_to_markdown(html),
+            "Notes:\n\n```\n#.#\n  x_y\n```\n\n- one **1**\n- two\n\n1. first\n"
+        );
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        assert_eq!(html_to_markdown("<p>a*b [c] # d</p>"), "a\\*b \\[c\\] # d\n");
+        assert_eq!(html_to_markdown("<p># not a heading</p>"), "\\# not a heading\n");
+        assert_eq!(html_to_markdown("<p>1. not a list</p>"), "1\\. not a list\n");
+    }
+
+    #[test]
+    fn test_quest_document() {
+        let markdown = quest_markdown(2024, 1, BASIC, Some(3));
+
+        assert!(markdown.starts_with("---\ntitle: \""));
+        assert!(markdown.contains("\nyear: 2024\nday: 1\nunlocked_parts: 3\n---\n"));
+        assert_eq!(markdown.matches("\n## Part ").count(), 3);
+        assert!(markdown.contains("## Part 1\n\n### Quest"));
+        assert!(markdown.contains("```\nABBAC\n```"));
+        assert!(!markdown.contains("PART 2"));
+    }
+}
@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
+use crate::extract::SamplePick;
+use crate::solver::SolverRun;
+
+/// Current user profile (`/api/user/me`)
 #[derive(Debug, Deserialize)]
 pub struct User {
     pub seed: i32,
 }
 
-#[derive(Debug, Deserialize)]
+/// Decryption keys for a quest; later keys appear as parts unlock
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuestKeys {
     pub key1: String,
     #[serde(default)]
@@ -15,6 +22,7 @@ pub struct QuestKeys {
 }
 
 impl QuestKeys {
+    /// Key for the given part, or an error if it hasn't been released
     pub fn get_key(&self, part: i32) -> Result<&str, String> {
         match part {
             1 => Ok(&self.key1),
@@ -25,14 +33,35 @@ impl QuestKeys {
             _ => Ok(&self.key1),
         }
     }
+
+    /// Whether the key for the given part has been released
+    pub fn is_unlocked(&self, part: i32) -> bool {
+        (1..=3).contains(&part) && self.get_key(part).is_ok()
+    }
+
+    /// Number of parts with keys available (1-3)
+    pub fn unlocked_parts(&self) -> i32 {
+        1 + self.key2.is_some() as i32 + self.key3.is_some() as i32
+    }
 }
 
+/// Request body for answer submission
 #[derive(Debug, Serialize)]
 pub struct AnswerPayload {
     pub answer: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// An HTTP request described rather than sent (dry run)
+#[derive(Debug, Serialize)]
+pub struct RequestPreview {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Server verdict for a submitted answer
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubmitResponse {
     pub correct: bool,
@@ -44,3 +73,138 @@ pub struct SubmitResponse {
     #[serde(default)]
     pub message: String,
 }
+
+/// A submission recorded in the local history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub part: i32,
+    pub answer: String,
+    pub correct: bool,
+    pub length_correct: bool,
+    pub first_correct: bool,
+    pub global_place: i64,
+    pub submitted_at: String,
+}
+
+/// Choices made for a quest that later fetches must respect
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestMetadata {
+    /// Hand-picked samples by part, used instead of automatic extraction
+    #[serde(default)]
+    pub sample_picks: BTreeMap<i32, Vec<SamplePick>>,
+}
+
+/// A stored sample and the expected answer it was paired with
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredSample {
+    /// Position of the sample within its part, starting at 1
+    pub number: usize,
+    pub path: PathBuf,
+    pub expected_answer: Option<String>,
+}
+
+/// Everything known locally about a single part of a quest
+#[derive(Debug, Serialize)]
+pub struct PartStatus {
+    pub part: i32,
+    pub unlocked: Option<bool>,
+    pub input_path: Option<PathBuf>,
+    pub samples: Vec<StoredSample>,
+    pub submissions: Vec<Submission>,
+}
+
+impl PartStatus {
+    /// Whether any recorded submission for this part was accepted
+    pub fn is_solved(&self) -> bool {
+        self.submissions.iter().any(|s| s.correct)
+    }
+}
+
+/// Everything known locally about a quest
+#[derive(Debug, Serialize)]
+pub struct QuestStatus {
+    pub year: i32,
+    pub day: i32,
+    pub title: Option<String>,
+    pub description_path: Option<PathBuf>,
+    pub parts: Vec<PartStatus>,
+}
+
+/// What a dry run would have done to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteAction {
+    Create,
+    Overwrite,
+    Unchanged,
+    Remove,
+}
+
+impl WriteAction {
+    /// Verb phrase used in dry-run reports
+    pub fn verb(&self) -> &'static str {
+        match self {
+            WriteAction::Create => "create",
+            WriteAction::Overwrite => "overwrite",
+            WriteAction::Unchanged => "leave unchanged",
+            WriteAction::Remove => "remove",
+        }
+    }
+}
+
+/// A file write or removal skipped in dry-run mode
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedWrite {
+    pub path: PathBuf,
+    pub action: WriteAction,
+    pub diff: Option<String>,
+}
+
+/// How a re-run compared with the accepted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressOutcome {
+    /// Solver still produces the accepted answer
+    Pass,
+    /// Solver produced a different answer
+    Fail,
+    /// Solver exited unsuccessfully
+    Error,
+    /// Solver was killed after exceeding the timeout
+    Timeout,
+    /// No stored input to run against
+    MissingInput,
+}
+
+impl RegressOutcome {
+    /// Whether this outcome counts as a regression
+    pub fn is_regression(&self) -> bool {
+        matches!(
+            self,
+            RegressOutcome::Fail | RegressOutcome::Error | RegressOutcome::Timeout
+        )
+    }
+}
+
+/// Re-run of one part that has an accepted answer
+#[derive(Debug, Clone, Serialize)]
+pub struct RegressResult {
+    pub year: i32,
+    pub day: i32,
+    pub part: i32,
+    pub expected: String,
+    pub run: Option<SolverRun>,
+    pub outcome: RegressOutcome,
+}
+
+/// A quest generated as a Cargo workspace member
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CargoMember {
+    pub package: String,
+    /// Workspace manifest the member was added to
+    pub manifest: PathBuf,
+    /// Member directory
+    pub path: PathBuf,
+    /// Generated sample tests
+    pub tests_file: PathBuf,
+}
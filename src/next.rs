@@ -0,0 +1,130 @@
+//! Picking the earliest part still to solve
+
+use log::info;
+
+use crate::client::EcClient;
+use crate::error::{EcError, Result};
+use crate::models::{QuestKeys, Submission};
+use crate::storage::Storage;
+
+/// Whether the part has an accepted answer on record
+pub fn solved_locally(submissions: &[Submission], part: i32) -> bool {
+    submissions.iter().any(|s| s.part == part && s.correct)
+}
+
+/// First unlocked part without an accepted answer
+///
+/// A part also counts as solved if the server has released the next one,
+/// which covers answers submitted outside ec-cli.
+pub fn unsolved_part(submissions: &[Submission], keys: &QuestKeys) -> Option<i32> {
+    (1..=3).find(|&part| {
+        keys.is_unlocked(part) && !solved_locally(submissions, part) && !keys.is_unlocked(part + 1)
+    })
+}
+
+/// Earliest unlocked part of the year without an accepted answer
+///
+/// Keys are fetched (and stored) only for quests not fully solved locally;
+/// the search stops at the first quest that hasn't been released.
+pub async fn find(client: &mut EcClient, storage: &Storage, year: i32) -> Result<Option<(i32, i32)>> {
+    for day in 1..=20 {
+        let submissions = storage.load_submissions(year, day)?;
+        if (1..=3).all(|part| solved_locally(&submissions, part)) {
+            continue;
+        }
+
+        let keys = match client.fetch_quest_keys(year, day).await {
+            Ok(keys) => keys,
+            Err(EcError::QuestNotAvailable { .. }) => break,
+            Err(e) => return Err(e),
+        };
+        storage.save_keys(year, day, &keys)?;
+
+        if let Some(part) = unsolved_part(&submissions, &keys) {
+            info!("Next unsolved part: {year}/{day} part {part}");
+            return Ok(Some((day, part)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Files stored by [`prepare`]
+#[cfg(feature = "cli")]
+#[derive(Debug)]
+pub struct PreparedPart {
+    pub description: String,
+    pub description_path: std::path::PathBuf,
+    pub input_path: std::path::PathBuf,
+    pub samples: Vec<crate::models::StoredSample>,
+    /// Every file written, including samples and regenerated tests
+    pub files: Vec<std::path::PathBuf>,
+}
+
+/// Fetch a part's input and refreshed description along with its samples
+#[cfg(feature = "cli")]
+pub async fn prepare(
+    client: &mut EcClient,
+    storage: &Storage,
+    year: i32,
+    day: i32,
+    part: i32,
+) -> Result<PreparedPart> {
+    let description = client.fetch_description(year, day).await?;
+    let description_path = storage.save_description(year, day, &description)?;
+    let mut files = vec![description_path.clone()];
+    files.extend(storage.save_samples(year, day, &description, None)?);
+    files.extend(crate::scaffold::regenerate_sample_tests(storage, year, day)?);
+
+    let input = client.fetch_input(year, day, part).await?;
+    let input_path = storage.save_input(year, day, part, &input)?;
+    files.push(input_path.clone());
+
+    Ok(PreparedPart {
+        samples: storage.stored_samples(year, day, part)?,
+        description,
+        description_path,
+        input_path,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(part: i32, correct: bool) -> Submission {
+        Submission {
+            part,
+            answer: "42".to_string(),
+            correct,
+            length_correct: correct,
+            first_correct: correct,
+            global_place: 0,
+            submitted_at: String::new(),
+        }
+    }
+
+    fn keys(unlocked: i32) -> QuestKeys {
+        QuestKeys {
+            key1: "k1".to_string(),
+            key2: (unlocked >= 2).then(|| "k2".to_string()),
+            key3: (unlocked >= 3).then(|| "k3".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_unsolved_part() {
+        assert_eq!(unsolved_part(&[], &keys(1)), Some(1));
+        assert_eq!(unsolved_part(&[submission(1, false)], &keys(1)), Some(1));
+        assert_eq!(unsolved_part(&[submission(1, true)], &keys(1)), None);
+        assert_eq!(unsolved_part(&[submission(1, true)], &keys(2)), Some(2));
+    }
+
+    #[test]
+    fn test_released_part_counts_as_solved() {
+        // Parts 1 and 2 were solved elsewhere, so the server released part 3
+        assert_eq!(unsolved_part(&[], &keys(3)), Some(3));
+        assert_eq!(unsolved_part(&[submission(3, true)], &keys(3)), None);
+    }
+}
@@ -0,0 +1,84 @@
+use log::debug;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Show text through `$PAGER` (or `less -R`) when it doesn't fit the terminal
+///
+/// Falls back to printing when stdout isn't a terminal, the text fits, or the
+/// pager can't be started.
+pub fn page(text: &str, enabled: bool) -> io::Result<()> {
+    if enabled && io::stdout().is_terminal() {
+        let height = term_size::dimensions().map(|(_, h)| h).unwrap_or(24);
+        if text.lines().count() >= height && spawn(text)? {
+            return Ok(());
+        }
+    }
+
+    print!("{text}");
+    Ok(())
+}
+
+/// Pipe text into the pager; false if it couldn't be started or run
+fn spawn(text: &str) -> io::Result<bool> {
+    let mut command = match std::env::var("PAGER") {
+        Ok(pager) if !pager.trim().is_empty() => {
+            let mut command = Command::new("sh");
+            command.args(["-c", &pager]);
+            command
+        }
+        _ => {
+            let mut command = Command::new("less");
+            command.arg("-R");
+            command
+        }
+    };
+    // Keep colours and don't clear the screen on exit, as git does
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    debug!("Paging through {command:?}");
+    let mut child = match command.stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            debug!("Could not start pager: {e}");
+            return Ok(false);
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe; that's fine
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    let status = child.wait()?;
+
+    // The shell exits with 127 (not found) or 126 (not executable) when the
+    // pager never ran, so the text still needs printing
+    if matches!(status.code(), Some(126 | 127)) {
+        debug!("Pager could not run ({status})");
+        return Ok(false);
+    }
+    if !status.success() {
+        debug!("Pager exited with {status}");
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_pager_falls_back() {
+        // No other test reads this variable
+        std::env::set_var("PAGER", "ec-cli-no-such-pager");
+        assert!(!spawn("text").unwrap());
+        std::env::set_var("PAGER", "cat > /dev/null");
+        assert!(spawn("text").unwrap());
+        std::env::remove_var("PAGER");
+    }
+}
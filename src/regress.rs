@@ -0,0 +1,119 @@
+//! Re-running solved parts to catch regressions
+
+use log::info;
+
+use crate::error::Result;
+use crate::models::{RegressOutcome, RegressResult};
+use crate::solver::{SolverCommand, SolverRun, SolverStatus};
+use crate::storage::Storage;
+
+/// Re-run the solver on every part with an accepted answer
+///
+/// `year` limits the check to one event. Parts without a stored input are
+/// reported as [`RegressOutcome::MissingInput`] rather than skipped.
+pub fn run(storage: &Storage, year: Option<i32>, solver: &SolverCommand) -> Result<Vec<RegressResult>> {
+    let mut results = Vec::new();
+
+    for (quest_year, day) in storage.quests_with_submissions()? {
+        if year.is_some_and(|y| y != quest_year) {
+            continue;
+        }
+
+        let submissions = storage.load_submissions(quest_year, day)?;
+        for part in 1..=3 {
+            let Some(accepted) = submissions.iter().rfind(|s| s.part == part && s.correct) else {
+                continue;
+            };
+
+            let (run, outcome) = if storage.has_input(quest_year, day, part) {
+                info!("Checking {quest_year}/{day} part {part}...");
+                let input = storage.input_file(quest_year, day, part);
+                let run = solver.run(quest_year, day, part, &input)?;
+                let outcome = outcome(&run, &accepted.answer);
+                (Some(run), outcome)
+            } else {
+                (None, RegressOutcome::MissingInput)
+            };
+
+            results.push(RegressResult {
+                year: quest_year,
+                day,
+                part,
+                expected: accepted.answer.clone(),
+                run,
+                outcome,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Classify a run against the accepted answer
+pub fn outcome(run: &SolverRun, expected: &str) -> RegressOutcome {
+    match run.status {
+        SolverStatus::TimedOut => RegressOutcome::Timeout,
+        SolverStatus::Failed(_) | SolverStatus::OutputLimitExceeded => RegressOutcome::Error,
+        SolverStatus::Success if run.answer.as_deref() == Some(expected) => RegressOutcome::Pass,
+        SolverStatus::Success => RegressOutcome::Fail,
+    }
+}
+
+/// Whether any result counts as a regression
+pub fn regressed(results: &[RegressResult]) -> bool {
+    results.iter().any(|r| r.outcome.is_regression())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Submission;
+
+    fn accept(storage: &Storage, year: i32, day: i32, part: i32, answer: &str) {
+        let submission = Submission {
+            part,
+            answer: answer.to_string(),
+            correct: true,
+            length_correct: true,
+            first_correct: true,
+            global_place: 0,
+            submitted_at: String::new(),
+        };
+        storage.record_submission(year, day, &submission).unwrap();
+    }
+
+    #[test]
+    fn test_run_classifies_parts() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        accept(&storage, 2024, 1, 1, "42");
+        accept(&storage, 2024, 1, 2, "8");
+        accept(&storage, 2024, 1, 3, "1");
+        accept(&storage, 2025, 1, 1, "42");
+        storage.save_input(2024, 1, 1, "42\n").unwrap();
+        storage.save_input(2024, 1, 2, "7\n").unwrap();
+
+        let results = run(&storage, Some(2024), &SolverCommand::new("cat")).unwrap();
+        let outcomes: Vec<_> = results.iter().map(|r| (r.part, r.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                (1, RegressOutcome::Pass),
+                (2, RegressOutcome::Fail),
+                (3, RegressOutcome::MissingInput),
+            ]
+        );
+        assert!(results.iter().all(|r| r.year == 2024));
+        assert!(regressed(&results));
+    }
+
+    #[test]
+    fn test_failed_solver_is_an_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let input = temp.path().join("input.txt");
+        std::fs::write(&input, "42\n").unwrap();
+
+        let run = SolverCommand::new("echo 42; exit 3").run(2024, 1, 1, &input).unwrap();
+        assert_eq!(outcome(&run, "42"), RegressOutcome::Error);
+    }
+}
@@ -0,0 +1,361 @@
+//! Styled terminal rendering of quest descriptions
+//!
+//! Walks the description's DOM and lays it out for the terminal: headings and
+//! part banners stand out, `<b>`/`<em>`/`<code>` keep their emphasis, and
+//! `<pre>` blocks are framed with a gutter and never re-wrapped. Without
+//! colour the layout is the same, minus the escape codes.
+
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+use crate::display;
+
+/// Environment variable that disables colour when set to anything non-empty
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Whether to emit colour: only on a terminal, and never with `NO_COLOR`
+pub fn use_color(is_terminal: bool) -> bool {
+    is_terminal && std::env::var_os(NO_COLOR_ENV).is_none_or(|v| v.is_empty())
+}
+
+/// Render a combined description, one "Part N" banner per part
+pub fn render_description(description: &str, width: usize, color: bool) -> String {
+    let parts: Vec<(i32, &str)> = (1..).zip(display::split_parts(description)).collect();
+    if parts.len() == 1 {
+        return render_html(parts[0].1, width, color);
+    }
+    render_parts(&parts, width, color)
+}
+
+/// Render selected parts, each under its "Part N" banner
+pub fn render_parts(parts: &[(i32, &str)], width: usize, color: bool) -> String {
+    parts
+        .iter()
+        .map(|&(part, html)| {
+            format!("{}{}", part_banner(part, width, color), render_html(html, width, color))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render one HTML fragment
+pub fn render_html(html: &str, width: usize, color: bool) -> String {
+    let document = Html::parse_fragment(html);
+    let mut renderer = Renderer {
+        width: width.max(20),
+        color,
+        out: String::new(),
+        inline: Vec::new(),
+    };
+
+    renderer.walk(document.tree.root(), Style::default());
+    renderer.block();
+
+    renderer.out.trim_end().to_string() + "\n"
+}
+
+fn part_banner(part: i32, width: usize, color: bool) -> String {
+    let label = format!("── Part {part} ");
+    let rule = "─".repeat(width.saturating_sub(label.chars().count()).min(60));
+    let style = Style {
+        bold: true,
+        banner: true,
+        ..Style::default()
+    };
+    format!("{}\n\n", paint(&format!("{label}{rule}"), style, color))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    code: bool,
+    heading: bool,
+    banner: bool,
+    dim: bool,
+}
+
+impl Style {
+    fn sgr(self) -> String {
+        let codes: Vec<&str> = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.code, "33"),
+            (self.heading, "36"),
+            (self.banner, "35"),
+        ]
+        .into_iter()
+        .filter_map(|(on, code)| on.then_some(code))
+        .collect();
+
+        if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        }
+    }
+}
+
+fn paint(text: &str, style: Style, color: bool) -> String {
+    let sgr = style.sgr();
+    if !color || sgr.is_empty() {
+        text.to_string()
+    } else {
+        format!("{sgr}{text}\x1b[0m")
+    }
+}
+
+/// Text with a style per character, so words can be split across tags
+type Styled = Vec<(char, Style)>;
+
+struct Renderer {
+    width: usize,
+    color: bool,
+    out: String,
+    /// Pending inline content of the current block
+    inline: Styled,
+}
+
+impl Renderer {
+    fn walk(&mut self, node: NodeRef<Node>, style: Style) {
+        match node.value() {
+            Node::Text(text) => self.inline.extend(text.chars().map(|c| (c, style))),
+            Node::Element(element) => match element.name() {
+                "script" | "style" => {}
+                "br" => self.inline.push(('\n', style)),
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    self.block();
+                    let heading = Style {
+                        bold: true,
+                        heading: true,
+                        ..style
+                    };
+                    self.walk_children(node, heading);
+                    self.block();
+                }
+                "pre" => {
+                    self.block();
+                    let mut text = Styled::new();
+                    collect(node, Style { code: true, ..style }, &mut text);
+                    self.pre(text);
+                    self.blank_line();
+                }
+                "li" => {
+                    self.flush("", "");
+                    self.walk_children(node, style);
+                    self.flush("  • ", "    ");
+                }
+                "p" | "div" | "section" | "article" | "blockquote" | "ul" | "ol" | "table"
+                | "tr" => {
+                    self.block();
+                    self.walk_children(node, style);
+                    self.block();
+                }
+                "b" | "strong" => self.walk_children(node, Style { bold: true, ..style }),
+                "em" | "i" => self.walk_children(node, Style { italic: true, ..style }),
+                "code" => self.walk_children(node, Style { code: true, ..style }),
+                "a" | "u" => self.walk_children(node, Style { underline: true, ..style }),
+                _ => self.walk_children(node, style),
+            },
+            _ => self.walk_children(node, style),
+        }
+    }
+
+    fn walk_children(&mut self, node: NodeRef<Node>, style: Style) {
+        for child in node.children() {
+            self.walk(child, style);
+        }
+    }
+
+    /// End the current block: flush its text and leave a blank line
+    fn block(&mut self) {
+        self.flush("", "");
+        self.blank_line();
+    }
+
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// Wrap the pending inline content, prefixing the first and following lines
+    fn flush(&mut self, first_prefix: &str, prefix: &str) {
+        let inline = std::mem::take(&mut self.inline);
+        let lines = wrap(&inline, self.width.saturating_sub(prefix.chars().count()).max(10));
+        if lines.is_empty() {
+            return;
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            self.out.push_str(if i == 0 { first_prefix } else { prefix });
+            self.out.push_str(&self.styled(line));
+            self.out.push('\n');
+        }
+    }
+
+    /// A preformatted block, kept verbatim behind a gutter
+    fn pre(&mut self, text: Styled) {
+        let gutter = paint("│ ", Style { dim: true, ..Style::default() }, self.color);
+
+        let blank = |line: &&[(char, Style)]| line.iter().all(|(c, _)| c.is_whitespace());
+        let mut lines: Vec<&[(char, Style)]> = text.split(|(c, _)| *c == '\n').collect();
+        while lines.first().is_some_and(blank) {
+            lines.remove(0);
+        }
+        while lines.last().is_some_and(blank) {
+            lines.pop();
+        }
+
+        for line in lines {
+            self.out.push_str("  ");
+            self.out.push_str(&gutter);
+            self.out.push_str(&self.styled(line));
+            self.out.push('\n');
+        }
+    }
+
+    /// Emit escape codes only where the style changes
+    fn styled(&self, text: &[(char, Style)]) -> String {
+        let mut out = String::new();
+        let mut current = Style::default();
+
+        for &(c, style) in text {
+            if self.color && style != current {
+                if current != Style::default() {
+                    out.push_str("\x1b[0m");
+                }
+                out.push_str(&style.sgr());
+                current = style;
+            }
+            out.push(c);
+        }
+        if self.color && current != Style::default() {
+            out.push_str("\x1b[0m");
+        }
+
+        out
+    }
+}
+
+/// Collect the text of a subtree verbatim, with styles
+fn collect(node: NodeRef<Node>, style: Style, out: &mut Styled) {
+    for child in node.children() {
+        match child.value() {
+            Node::Text(text) => out.extend(text.chars().map(|c| (c, style))),
+            Node::Element(element) => {
+                let style = match element.name() {
+                    "b" | "strong" => Style { bold: true, ..style },
+                    "em" | "i" => Style { italic: true, ..style },
+                    _ => style,
+                };
+                collect(child, style, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Greedy word wrap; whitespace collapses and `\n` forces a break
+fn wrap(text: &[(char, Style)], width: usize) -> Vec<Styled> {
+    let mut wrapper = Wrapper {
+        width,
+        ..Wrapper::default()
+    };
+
+    for &(c, style) in text {
+        if c == '\n' {
+            wrapper.end_word();
+            wrapper.end_line();
+        } else if c.is_whitespace() {
+            wrapper.end_word();
+            // Keep the style of the gap, so underlined links stay continuous
+            wrapper.space.get_or_insert(style);
+        } else {
+            wrapper.word.push((c, style));
+        }
+    }
+    wrapper.end_word();
+    if !wrapper.line.is_empty() {
+        wrapper.end_line();
+    }
+
+    // Drop blank lines left by leading breaks
+    let first = wrapper.lines.iter().position(|l| !l.is_empty()).unwrap_or(wrapper.lines.len());
+    wrapper.lines.split_off(first)
+}
+
+#[derive(Default)]
+struct Wrapper {
+    width: usize,
+    lines: Vec<Styled>,
+    line: Styled,
+    word: Styled,
+    space: Option<Style>,
+}
+
+impl Wrapper {
+    fn end_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        if !self.line.is_empty() && self.line.len() + 1 + self.word.len() > self.width {
+            self.end_line();
+        }
+        if !self.line.is_empty() {
+            self.line.push((' ', self.space.unwrap_or_default()));
+        }
+        self.line.append(&mut self.word);
+        self.space = None;
+    }
+
+    fn end_line(&mut self) {
+        self.lines.push(std::mem::take(&mut self.line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraps_paragraphs_but_not_pre() {
+        let long = "word ".repeat(20);
+        let html = format!("<p>{long}</p><pre>{}</pre>", "x".repeat(50));
+        let text = render_html(&html, 30, false);
+
+        assert!(text.lines().take(3).all(|l| l.chars().count() <= 30));
+        assert!(text.contains(&format!("│ {}", "x".repeat(50))));
+    }
+
+    #[test]
+    fn test_plain_output_has_no_escape_codes() {
+        let html = "<h2>Quest 1</h2><p>The answer is <b>42</b> and <em>not</em> <code>41</code>.</p>";
+        let text = render_html(html, 80, false);
+
+        assert_eq!(text, "Quest 1\n\nThe answer is 42 and not 41.\n");
+    }
+
+    #[test]
+    fn test_colour_marks_bold_and_keeps_spacing() {
+        let text = render_html("<p>is <b>42</b>.</p>", 80, true);
+        assert_eq!(text, "is \x1b[1m42\x1b[0m.\n");
+    }
+
+    #[test]
+    fn test_parts_get_banners() {
+        let banner = |n: i32| format!("\n\n{}\n PART {n} \n{}\n\n", "=".repeat(80), "=".repeat(80));
+        let description = format!("<p>one</p>{}<p>two</p>", banner(2));
+        let text = render_description(&description, 40, false);
+
+        assert!(text.starts_with("── Part 1 "));
+        assert!(text.contains("── Part 2 "));
+
+        let latest = render_parts(&[(2, "<p>two</p>")], 40, false);
+        assert!(latest.starts_with("── Part 2 "));
+        assert!(!latest.contains("one"));
+    }
+}
@@ -0,0 +1,507 @@
+//! Generate solution skeletons from templates
+//!
+//! A template is a directory tree whose file names and contents may contain
+//! placeholders:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{{year}}`, `{{day}}` | Quest year and day |
+//! | `{{day2}}` | Day padded to two digits |
+//! | `{{title}}` | Quest title from the stored description |
+//! | `{{input1}}` … `{{input3}}` | Input paths from [`Storage`] |
+//! | `{{sample1}}` … `{{sample3}}` | First sample path of each part from [`Storage`] |
+//! | `{{input1_rs}}`, `{{sample1_py}}`, … | The same paths as Rust or Python string literals |
+//!
+//! A `{{#samples}}…{{/samples}}` block is repeated for every stored sample with
+//! an expected answer, with `{{part}}`, `{{index}}`, `{{sample}}` and
+//! `{{expected}}` available inside it. `{{sample}}` and `{{expected}}` are the
+//! raw text; `{{sample_rs}}`/`{{expected_rs}}` and `{{sample_py}}`/
+//! `{{expected_py}}` are complete Rust and Python string literals that keep
+//! quotes and backslashes intact.
+//!
+//! With `new --cargo` the quest becomes a member of the enclosing Cargo
+//! workspace instead. Its sample tests live in a generated `samples.rs` that
+//! `fetch` rewrites whenever new parts unlock.
+//!
+//! Only built with the `cli` feature.
+
+use log::{info, warn};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::display;
+use crate::error::{EcError, Result};
+use crate::models::CargoMember;
+use crate::storage::Storage;
+
+/// Environment variable pointing at a directory of user templates
+pub const TEMPLATE_DIR_ENV: &str = "EC_TEMPLATE_DIR";
+
+/// Templates compiled into the binary, keyed by language
+const BUILTIN: &[(&str, &[(&str, &str)])] = &[
+    (
+        "rust",
+        &[(
+            "src/bin/q{{day2}}.rs",
+            include_str!("../templates/rust/src/bin/q{{day2}}.rs"),
+        )],
+    ),
+    (
+        "python",
+        &[(
+            "q{{day2}}.py",
+            include_str!("../templates/python/q{{day2}}.py"),
+        )],
+    ),
+];
+
+/// Files of a generated workspace member that belong to the user
+const CARGO_MEMBER: &[(&str, &str)] = &[
+    ("Cargo.toml", include_str!("../templates/cargo/Cargo.toml")),
+    ("src/main.rs", include_str!("../templates/cargo/src/main.rs")),
+];
+
+/// Generated sample tests of a workspace member, rewritten on every update
+const CARGO_SAMPLE_TESTS: &str = include_str!("../templates/cargo/src/samples.rs");
+
+/// Names of the built-in templates
+pub fn builtin_languages() -> Vec<&'static str> {
+    BUILTIN.iter().map(|(lang, _)| *lang).collect()
+}
+
+/// A sample with a known answer, pasted into generated tests
+#[derive(Debug, Clone)]
+pub struct SampleCase {
+    pub part: i32,
+    /// Number of the sample within its part, starting at 1
+    pub index: usize,
+    pub input: String,
+    pub expected: String,
+}
+
+/// Values substituted into a template
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    vars: BTreeMap<&'static str, String>,
+    samples: Vec<SampleCase>,
+}
+
+impl TemplateContext {
+    /// Collect everything stored locally for a quest
+    pub fn from_storage(storage: &Storage, year: i32, day: i32) -> Result<Self> {
+        let title = if storage.has_description(year, day) {
+            display::extract_title(&storage.load_description(year, day)?)
+        } else {
+            warn!("No description stored for {year}/{day}; fetch it first for a title and samples");
+            None
+        };
+
+        let mut vars = BTreeMap::new();
+        vars.insert("year", year.to_string());
+        vars.insert("day", day.to_string());
+        vars.insert("day2", format!("{day:02}"));
+        vars.insert("title", title.unwrap_or_else(|| format!("Quest {day}")));
+        // Absolute, so generated code works wherever it is run from
+        let absolute = |path: PathBuf| std::path::absolute(&path).unwrap_or(path).display().to_string();
+        let paths = [
+            (["input1", "input1_rs", "input1_py"], absolute(storage.input_file(year, day, 1))),
+            (["input2", "input2_rs", "input2_py"], absolute(storage.input_file(year, day, 2))),
+            (["input3", "input3_rs", "input3_py"], absolute(storage.input_file(year, day, 3))),
+            (["sample1", "sample1_rs", "sample1_py"], absolute(storage.sample_file(year, day, 1, 1))),
+            (["sample2", "sample2_rs", "sample2_py"], absolute(storage.sample_file(year, day, 2, 1))),
+            (["sample3", "sample3_rs", "sample3_py"], absolute(storage.sample_file(year, day, 3, 1))),
+        ];
+        for ([raw, rust, python], path) in paths {
+            vars.insert(rust, rust_literal(&path));
+            vars.insert(python, python_literal(&path));
+            vars.insert(raw, path);
+        }
+
+        let mut samples = Vec::new();
+        for part in 1..=3 {
+            for stored in storage.stored_samples(year, day, part)? {
+                let n = stored.number;
+                match stored.expected_answer {
+                    Some(expected) => samples.push(SampleCase {
+                        part,
+                        index: n,
+                        input: storage.load_sample(year, day, part, n)?,
+                        expected,
+                    }),
+                    None => warn!("Sample {n} of part {part} has no expected answer; no test generated"),
+                }
+            }
+        }
+
+        Ok(Self { vars, samples })
+    }
+
+    /// Add or replace a placeholder
+    pub fn set(&mut self, name: &'static str, value: String) {
+        self.vars.insert(name, value);
+    }
+
+    /// Samples that will be expanded into `{{#samples}}` blocks
+    pub fn samples(&self) -> &[SampleCase] {
+        &self.samples
+    }
+
+    /// Substitute placeholders in a template string
+    pub fn render(&self, template: &str) -> String {
+        let block = Regex::new(r"(?s)\{\{#samples\}\}(.*?)\{\{/samples\}\}").unwrap();
+        let expanded = block.replace_all(template, |cap: &regex::Captures| {
+            self.samples
+                .iter()
+                .map(|sample| {
+                    cap[1]
+                        .replace("{{part}}", &sample.part.to_string())
+                        .replace("{{index}}", &sample.index.to_string())
+                        .replace("{{sample_rs}}", &rust_literal(&sample.input))
+                        .replace("{{expected_rs}}", &rust_literal(&sample.expected))
+                        .replace("{{sample_py}}", &python_literal(&sample.input))
+                        .replace("{{expected_py}}", &python_literal(&sample.expected))
+                        .replace("{{sample}}", &sample.input)
+                        .replace("{{expected}}", &sample.expected)
+                })
+                .collect::<String>()
+        });
+
+        self.vars
+            .iter()
+            .fold(expanded.into_owned(), |text, (name, value)| {
+                text.replace(&format!("{{{{{name}}}}}"), value)
+            })
+    }
+}
+
+/// A Rust raw string literal with enough `#`s that no `"#…` inside can end it
+fn rust_literal(text: &str) -> String {
+    let mut hashes = String::new();
+    while text.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}\"{text}\"{hashes}")
+}
+
+/// A Python string literal: raw where that can hold the text (triple-quoted
+/// when it spans lines, so samples stay readable), and escaped otherwise
+fn python_literal(text: &str) -> String {
+    let raw_safe = !text.contains('\r') && !text.ends_with('\\');
+    if raw_safe && !text.contains(['\n', '"']) {
+        format!("r\"{text}\"")
+    } else if raw_safe && !text.contains("\"\"\"") && !text.ends_with('"') {
+        format!("r\"\"\"{text}\"\"\"")
+    } else {
+        // A JSON string is also a valid Python literal
+        serde_json::Value::from(text).to_string()
+    }
+}
+
+/// Files a template would generate, relative to the output directory
+pub fn load_template(lang: &str, template_dir: Option<&Path>) -> Result<Vec<(PathBuf, String)>> {
+    if let Some(dir) = template_dir {
+        let root = dir.join(lang);
+        if root.is_dir() {
+            info!("Using template {root:?}");
+            let mut files = Vec::new();
+            collect_files(&root, &root, &mut files)?;
+            return Ok(files);
+        }
+    }
+
+    BUILTIN
+        .iter()
+        .find(|(name, _)| *name == lang)
+        .map(|(_, files)| {
+            files
+                .iter()
+                .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+                .collect()
+        })
+        .ok_or_else(|| {
+            EcError::InvalidArgument(format!(
+                "no template for {lang:?} (built-in: {}; or add {lang}/ to ${TEMPLATE_DIR_ENV})",
+                builtin_languages().join(", ")
+            ))
+        })
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            files.push((relative, fs::read_to_string(&path)?));
+        }
+    }
+
+    Ok(())
+}
+
+/// Outcome of generating one file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaffoldAction {
+    Created,
+    /// A generated file was rewritten
+    Updated,
+    /// The file already existed and was left untouched
+    Skipped,
+}
+
+/// Render a template into `out_dir`, never overwriting existing files
+pub fn scaffold(
+    storage: &Storage,
+    files: &[(PathBuf, String)],
+    context: &TemplateContext,
+    out_dir: &Path,
+) -> Result<Vec<(PathBuf, ScaffoldAction)>> {
+    let mut results = Vec::new();
+
+    for (relative, content) in files {
+        let path = out_dir.join(context.render(&relative.to_string_lossy()));
+        if path.exists() {
+            warn!("{path:?} already exists, leaving it untouched");
+            results.push((path, ScaffoldAction::Skipped));
+            continue;
+        }
+
+        storage.save_file(&path, &context.render(content))?;
+        info!("Created {path:?}");
+        results.push((path, ScaffoldAction::Created));
+    }
+
+    Ok(results)
+}
+
+/// Find the nearest `Cargo.toml` with a `[workspace]` table at or above `start`
+pub fn find_workspace(start: &Path) -> Result<PathBuf> {
+    let start = fs::canonicalize(start)?;
+
+    for dir in start.ancestors() {
+        let manifest = dir.join("Cargo.toml");
+        if manifest.is_file() {
+            let document = parse_manifest(&manifest)?;
+            if document.contains_key("workspace") {
+                return Ok(manifest);
+            }
+        }
+    }
+
+    Err(EcError::InvalidArgument(format!(
+        "no Cargo workspace found at or above {start:?} (add a [workspace] table to your Cargo.toml)"
+    )))
+}
+
+fn parse_manifest(path: &Path) -> Result<DocumentMut> {
+    fs::read_to_string(path)?
+        .parse::<DocumentMut>()
+        .map_err(|e| EcError::InvalidArgument(format!("cannot parse {path:?}: {e}")))
+}
+
+/// Add `member` to `workspace.members`, keeping the rest of the manifest intact
+///
+/// Returns the edited manifest, or None when the member is already listed or
+/// matched by one of the existing glob entries.
+pub fn add_workspace_member(manifest: &str, member: &str) -> Result<Option<String>> {
+    let mut document = manifest
+        .parse::<DocumentMut>()
+        .map_err(|e| EcError::InvalidArgument(format!("cannot parse workspace manifest: {e}")))?;
+
+    let workspace = document["workspace"]
+        .as_table_like_mut()
+        .ok_or_else(|| EcError::InvalidArgument("manifest has no [workspace] table".to_string()))?;
+    let members = workspace
+        .entry("members")
+        .or_insert(Item::Value(Value::Array(Default::default())))
+        .as_array_mut()
+        .ok_or_else(|| EcError::InvalidArgument("workspace.members is not an array".to_string()))?;
+
+    let already_listed = members.iter().filter_map(|m| m.as_str()).any(|existing| {
+        existing.trim_end_matches('/') == member
+            || glob::Pattern::new(existing).is_ok_and(|pattern| pattern.matches(member))
+    });
+    if already_listed {
+        return Ok(None);
+    }
+
+    members.push(member);
+    Ok(Some(document.to_string()))
+}
+
+/// Generate the quest as a member of the enclosing Cargo workspace
+///
+/// The member's manifest and `main.rs` are only created when missing; the
+/// sample tests are always regenerated.
+pub fn scaffold_cargo(
+    storage: &Storage,
+    context: &TemplateContext,
+    year: i32,
+    day: i32,
+    out_dir: &Path,
+) -> Result<(CargoMember, Vec<(PathBuf, ScaffoldAction)>)> {
+    let manifest = find_workspace(out_dir)?;
+    let root = manifest.parent().unwrap_or(Path::new("."));
+    let relative = format!("{year}/q{day:02}");
+    let member = CargoMember {
+        package: format!("ec{year}-q{day:02}"),
+        manifest: manifest.clone(),
+        path: root.join(&relative),
+        tests_file: root.join(&relative).join("src").join("samples.rs"),
+    };
+
+    let mut context = context.clone();
+    context.set("package", member.package.clone());
+
+    let files: Vec<(PathBuf, String)> = CARGO_MEMBER
+        .iter()
+        .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+        .collect();
+    let mut results = scaffold(storage, &files, &context, &member.path)?;
+    results.push(write_sample_tests(storage, &context, &member)?);
+
+    if let Some(edited) = add_workspace_member(&fs::read_to_string(&manifest)?, &relative)? {
+        storage.save_file(&manifest, &edited)?;
+        info!("Added {relative} to workspace members in {manifest:?}");
+        results.push((manifest, ScaffoldAction::Updated));
+    }
+
+    storage.record_cargo_member(year, day, &member)?;
+    Ok((member, results))
+}
+
+fn write_sample_tests(
+    storage: &Storage,
+    context: &TemplateContext,
+    member: &CargoMember,
+) -> Result<(PathBuf, ScaffoldAction)> {
+    let action = if member.tests_file.exists() {
+        ScaffoldAction::Updated
+    } else {
+        ScaffoldAction::Created
+    };
+
+    storage.save_file(&member.tests_file, &context.render(CARGO_SAMPLE_TESTS))?;
+    Ok((member.tests_file.clone(), action))
+}
+
+/// Rewrite the sample tests of a quest generated with `new --cargo`
+///
+/// Does nothing for quests without a generated workspace member.
+pub fn regenerate_sample_tests(storage: &Storage, year: i32, day: i32) -> Result<Option<PathBuf>> {
+    let Some(member) = storage.load_cargo_member(year, day)? else {
+        return Ok(None);
+    };
+    if !member.path.is_dir() {
+        warn!("Workspace member {:?} no longer exists; not updating its tests", member.path);
+        return Ok(None);
+    }
+
+    let context = TemplateContext::from_storage(storage, year, day)?;
+    let (path, _) = write_sample_tests(storage, &context, &member)?;
+    info!(
+        "Regenerated {} sample test(s) in {path:?}",
+        context.samples().len()
+    );
+
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(samples: Vec<SampleCase>) -> TemplateContext {
+        let mut vars = BTreeMap::new();
+        vars.insert("day", "5".to_string());
+        vars.insert("day2", "05".to_string());
+        TemplateContext { vars, samples }
+    }
+
+    #[test]
+    fn test_render_repeats_sample_block() {
+        let context = context(vec![
+            SampleCase { part: 1, index: 1, input: "1 2".into(), expected: "3".into() },
+            SampleCase { part: 3, index: 2, input: "4 5".into(), expected: "9".into() },
+        ]);
+        let rendered = context.render(
+            "q{{day2}}:{{#samples}}[{{part}}.{{index}}|{{sample}}|{{expected}}]{{/samples}}",
+        );
+        assert_eq!(rendered, "q05:[1.1|1 2|3][3.2|4 5|9]");
+    }
+
+    #[test]
+    fn test_literals_keep_quotes_and_backslashes() {
+        let context = context(vec![SampleCase {
+            part: 1,
+            index: 1,
+            input: "a\\n\"#b".into(),
+            expected: "x\"y".into(),
+        }]);
+        let rendered = context.render("{{#samples}}{{sample_rs}} {{expected_rs}}{{/samples}}");
+        assert_eq!(rendered, "r##\"a\\n\"#b\"## r#\"x\"y\"#");
+
+        assert_eq!(python_literal("a\\n\nb"), "r\"\"\"a\\n\nb\"\"\"");
+        assert_eq!(python_literal("say \"\"\"hi\"\"\""), r#""say \"\"\"hi\"\"\"""#);
+        assert_eq!(python_literal("ends\\"), r#""ends\\""#);
+        assert_eq!(python_literal(r"C:\data\1-1.txt"), r#"r"C:\data\1-1.txt""#);
+    }
+
+    #[test]
+    fn test_paths_as_literals() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("odd \"dir\" \\ name");
+        let storage = Storage::new(Some(base));
+        let context = TemplateContext::from_storage(&storage, 2024, 5).unwrap();
+
+        let input = std::path::absolute(storage.input_file(2024, 5, 2)).unwrap().display().to_string();
+        assert_eq!(context.render("{{input2}}"), input);
+        assert_eq!(context.render("{{input2_rs}}"), rust_literal(&input));
+        assert_eq!(context.render("{{input2_py}}"), python_literal(&input));
+    }
+
+    #[test]
+    fn test_render_without_samples_drops_block() {
+        let rendered = context(Vec::new()).render("day {{day}}\n{{#samples}}test{{/samples}}end");
+        assert_eq!(rendered, "day 5\nend");
+    }
+
+    #[test]
+    fn test_add_workspace_member_preserves_manifest() {
+        let manifest = "# my solutions\n[workspace]\nmembers = [\"2023/*\"] # years\nresolver = \"2\"\n";
+
+        let edited = add_workspace_member(manifest, "2024/q05").unwrap().unwrap();
+        assert!(edited.starts_with("# my solutions\n[workspace]\n"));
+        assert!(edited.contains("# years"));
+        assert!(edited.contains("resolver = \"2\""));
+        assert!(edited.contains("\"2024/q05\""));
+
+        assert_eq!(add_workspace_member(&edited, "2024/q05").unwrap(), None);
+        assert_eq!(add_workspace_member(manifest, "2023/q01").unwrap(), None);
+    }
+
+    #[test]
+    fn test_add_workspace_member_creates_members() {
+        let edited = add_workspace_member("[workspace]\n", "2024/q05").unwrap().unwrap();
+        assert!(edited.contains("members = [\"2024/q05\"]"));
+
+        assert!(add_workspace_member("[package]\nname = \"x\"\n", "2024/q05").is_err());
+    }
+
+    #[test]
+    fn test_unknown_language_is_rejected() {
+        assert!(load_template("rust", None).is_ok());
+        assert!(matches!(
+            load_template("cobol", None),
+            Err(EcError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Full-text search over stored descriptions
+//!
+//! Each part is converted to text with [`display::html_to_text`] and its
+//! whitespace collapsed, so phrases match across line breaks. Every part with
+//! at least one match becomes a [`SearchHit`] carrying a snippet around the
+//! first match; [`rank`] orders hits by how well they match.
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::display;
+
+/// Characters of context kept on each side of the first match
+const CONTEXT: usize = 60;
+
+/// Extra score for a match in the quest title, which is a strong signal
+const TITLE_BONUS: usize = 10;
+
+/// A part of a quest that matched a search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchHit {
+    pub year: i32,
+    pub day: i32,
+    pub part: i32,
+    pub title: Option<String>,
+    /// Number of matches in the part's text
+    pub matches: usize,
+    pub score: usize,
+    /// One line of text around the first match
+    pub snippet: String,
+    /// Byte ranges of the matches within `snippet`
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// Search every part of one quest's description
+pub fn search_description(pattern: &Regex, year: i32, day: i32, description: &str) -> Vec<SearchHit> {
+    let title = display::extract_title(description);
+    let title_bonus = title
+        .as_deref()
+        .filter(|t| pattern.is_match(t))
+        .map_or(0, |_| TITLE_BONUS);
+
+    (1..)
+        .zip(display::split_parts(description))
+        .filter_map(|(part, html)| {
+            // Wide enough that wrapping never splits a phrase
+            let text = collapse(&display::html_to_text(html, 10_000));
+            let matches: Vec<_> = pattern.find_iter(&text).filter(|m| !m.is_empty()).collect();
+            let first = matches.first()?;
+
+            let start = floor_char_boundary(&text, first.start().saturating_sub(CONTEXT));
+            let end = ceil_char_boundary(&text, first.end() + CONTEXT);
+            // Don't cut words in half at the edges
+            let start = if start == 0 { 0 } else { text[start..first.start()].find(' ').map_or(start, |i| start + i + 1) };
+            let end = if end == text.len() { end } else { text[first.end()..end].rfind(' ').map_or(end, |i| first.end() + i) };
+
+            let prefix = if start > 0 { "…" } else { "" };
+            let suffix = if end < text.len() { "…" } else { "" };
+            let snippet = format!("{prefix}{}{suffix}", &text[start..end]);
+            let highlights = matches
+                .iter()
+                .filter(|m| m.start() >= start && m.end() <= end)
+                .map(|m| (m.start() - start + prefix.len(), m.end() - start + prefix.len()))
+                .collect();
+
+            Some(SearchHit {
+                year,
+                day,
+                part,
+                title: title.clone(),
+                matches: matches.len(),
+                score: matches.len() + title_bonus,
+                snippet,
+                highlights,
+            })
+        })
+        .collect()
+}
+
+/// Best hits first; ties go to the newest event, then quest and part order
+pub fn rank(hits: &mut [SearchHit]) {
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.year.cmp(&a.year))
+            .then(a.day.cmp(&b.day))
+            .then(a.part.cmp(&b.part))
+    });
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLE: &str = include_str!("../tests/fixtures/descriptions/multiple.html");
+
+    #[test]
+    fn test_hits_per_part_with_snippet() {
+        let pattern = Regex::new("(?i)example").unwrap();
+        let hits = search_description(&pattern, 2024, 7, MULTIPLE);
+
+        assert_eq!(hits.iter().map(|h| h.part).collect::<Vec<_>>(), vec![1, 2]);
+        for hit in &hits {
+            assert!(!hit.snippet.contains('\n'));
+            assert!(!hit.highlights.is_empty());
+            for &(start, end) in &hit.highlights {
+                assert!(hit.snippet[start..end].eq_ignore_ascii_case("example"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_snippet_is_trimmed_around_the_match() {
+        let description = format!("<p>{} needle {}</p>", "lead ".repeat(40), "tail ".repeat(40));
+        let hits = search_description(&Regex::new("needle").unwrap(), 2024, 1, &description);
+
+        let hit = &hits[0];
+        assert!(hit.snippet.starts_with("…lead"));
+        assert!(hit.snippet.ends_with("tail…"));
+        let (start, end) = hit.highlights[0];
+        assert_eq!(&hit.snippet[start..end], "needle");
+    }
+
+    #[test]
+    fn test_rank_prefers_more_matches_and_titles() {
+        let pattern = Regex::new("dice").unwrap();
+        let mut hits = [
+            search_description(&pattern, 2024, 3, "<p>dice</p>"),
+            search_description(&pattern, 2024, 9, "<p>dice dice dice</p>"),
+            search_description(&pattern, 2025, 1, "<h2>Quest 1: Dice</h2><p>dice</p>"),
+            search_description(&pattern, 2024, 12, "<h2>Quest 12: dice</h2><p>dice</p>"),
+        ]
+        .concat();
+        rank(&mut hits);
+
+        let order: Vec<_> = hits.iter().map(|h| (h.year, h.day)).collect();
+        assert_eq!(order, vec![(2024, 12), (2024, 9), (2025, 1), (2024, 3)]);
+    }
+}
@@ -0,0 +1,56 @@
+/// A solver for one quest
+///
+/// Each part has its own input, so `parse` runs once per part before the
+/// matching `partN` method. Parts that aren't solved yet keep the default
+/// implementation, which returns `None`, and are reported as skipped.
+///
+/// ```
+/// use ec_cli::Solution;
+///
+/// struct Quest1;
+///
+/// impl Solution for Quest1 {
+///     type Parsed = Vec<i64>;
+///
+///     fn parse(input: &str) -> Self::Parsed {
+///         input.lines().filter_map(|l| l.parse().ok()).collect()
+///     }
+///
+///     fn part1(numbers: &Self::Parsed) -> Option<String> {
+///         Some(numbers.iter().sum::<i64>().to_string())
+///     }
+/// }
+/// ```
+pub trait Solution {
+    /// Parsed form of a part's input
+    type Parsed;
+
+    /// Parse raw input text
+    fn parse(input: &str) -> Self::Parsed;
+
+    /// Solve part 1
+    fn part1(_parsed: &Self::Parsed) -> Option<String> {
+        None
+    }
+
+    /// Solve part 2
+    fn part2(_parsed: &Self::Parsed) -> Option<String> {
+        None
+    }
+
+    /// Solve part 3
+    fn part3(_parsed: &Self::Parsed) -> Option<String> {
+        None
+    }
+
+    /// Parse input and solve the given part (1-3)
+    fn solve(part: i32, input: &str) -> Option<String> {
+        let parsed = Self::parse(input);
+        match part {
+            1 => Self::part1(&parsed),
+            2 => Self::part2(&parsed),
+            3 => Self::part3(&parsed),
+            _ => None,
+        }
+    }
+}
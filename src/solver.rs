@@ -0,0 +1,578 @@
+//! Running external solver commands
+//!
+//! A solver command is a shell command template. The placeholders `{year}`,
+//! `{day}`, `{part}` and `{input}` are substituted before running it with
+//! `sh -c`; the input is also provided on stdin and through the `EC_YEAR`,
+//! `EC_DAY`, `EC_PART` and `EC_INPUT` environment variables. The answer is the
+//! last non-empty line the command prints.
+//!
+//! Every spawned process can be constrained with [`SolverLimits`]: a
+//! wall-clock timeout, an address-space limit (`RLIMIT_AS`, Unix only) and a
+//! cap on captured output. Processes run in their own process group so a
+//! timeout kills everything the solver started. Being in their own group also
+//! keeps them out of the terminal's Ctrl+C, so SIGINT and SIGTERM handlers
+//! kill the groups of running solvers before ec-cli itself exits (unless the
+//! program has installed handlers of its own).
+
+use log::{debug, warn};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+
+/// Environment variable holding the default solver command
+pub const SOLVER_ENV: &str = "EC_SOLVER";
+
+/// Resource limits applied to spawned solver processes
+#[derive(Debug, Clone, Default)]
+pub struct SolverLimits {
+    /// Wall-clock time after which the process group is killed
+    pub timeout: Option<Duration>,
+    /// Maximum address space in bytes (Unix only)
+    pub memory_bytes: Option<u64>,
+    /// Maximum captured stdout in bytes
+    pub max_output_bytes: Option<usize>,
+}
+
+/// How a solver process ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "code")]
+pub enum SolverStatus {
+    /// Exited with status 0
+    Success,
+    /// Exited non-zero or was killed by a signal
+    Failed(Option<i32>),
+    /// Killed after exceeding the timeout
+    TimedOut,
+    /// Killed after printing more than the output cap
+    OutputLimitExceeded,
+}
+
+impl std::fmt::Display for SolverStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverStatus::Success => write!(f, "succeeded"),
+            SolverStatus::Failed(Some(code)) => write!(f, "exited with code {code}"),
+            SolverStatus::Failed(None) => write!(f, "was killed by a signal"),
+            SolverStatus::TimedOut => write!(f, "timed out"),
+            SolverStatus::OutputLimitExceeded => write!(f, "exceeded the output limit"),
+        }
+    }
+}
+
+/// Captured result of a process run under [`run_limited`]
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    pub stdout: String,
+    /// Captured stderr, empty unless the command's stderr was piped
+    pub stderr: String,
+    pub duration: Duration,
+    pub peak_rss_kb: Option<u64>,
+    pub status: SolverStatus,
+}
+
+/// Spawn a command with stdout piped, enforce limits and wait for it
+pub fn run_limited(mut command: Command, limits: &SolverLimits) -> Result<ProcessOutput> {
+    command.stdout(Stdio::piped());
+    apply_process_limits(&mut command, limits);
+
+    install_interrupt_handlers();
+    let start = Instant::now();
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    let live = LiveGroup::register(pid);
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let output_exceeded = Arc::new(AtomicBool::new(false));
+
+    // Kill the whole process group if the deadline passes before we're told to stop
+    let (done, cancelled) = mpsc::channel::<()>();
+    let watchdog = limits.timeout.map(|timeout| {
+        let timed_out = Arc::clone(&timed_out);
+        std::thread::spawn(move || {
+            if cancelled.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                timed_out.store(true, Ordering::SeqCst);
+                kill_process_group(pid);
+            }
+        })
+    });
+
+    // Drain stdout on a separate thread so a chatty solver can't block on a full pipe
+    let pipe = child.stdout.take();
+    let max_output = limits.max_output_bytes;
+    let exceeded = Arc::clone(&output_exceeded);
+    let reader = std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(pipe) = pipe {
+            let cap = max_output.map_or(u64::MAX, |max| max as u64 + 1);
+            let _ = pipe.take(cap).read_to_end(&mut buffer);
+            if max_output.is_some_and(|max| buffer.len() > max) {
+                exceeded.store(true, Ordering::SeqCst);
+                kill_process_group(pid);
+                buffer.truncate(max_output.unwrap_or_default());
+            }
+        }
+        buffer
+    });
+    let stderr_reader = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            buffer
+        })
+    });
+
+    wait_for_exit(&mut child)?;
+    let duration = start.elapsed();
+
+    // The exited leader stays a zombie until it's reaped, so its group id
+    // can't be reused by an unrelated group while we still signal it
+    let _ = done.send(());
+    if let Some(watchdog) = watchdog {
+        let _ = watchdog.join();
+    }
+    // Clean up anything the solver left running in its group, which would
+    // otherwise hold stdout open
+    kill_process_group(pid);
+    let stdout = String::from_utf8_lossy(&reader.join().unwrap_or_default()).into_owned();
+    let stderr = stderr_reader
+        .map(|reader| String::from_utf8_lossy(&reader.join().unwrap_or_default()).into_owned())
+        .unwrap_or_default();
+    // Forget the group before reaping, after which its id may be reused
+    drop(live);
+    let (exit, peak_rss_kb) = wait_with_peak_rss(&mut child)?;
+
+    let status = if timed_out.load(Ordering::SeqCst) {
+        SolverStatus::TimedOut
+    } else if output_exceeded.load(Ordering::SeqCst) {
+        SolverStatus::OutputLimitExceeded
+    } else if exit.success() {
+        SolverStatus::Success
+    } else {
+        SolverStatus::Failed(exit.code())
+    };
+
+    Ok(ProcessOutput {
+        stdout,
+        stderr,
+        duration,
+        peak_rss_kb,
+        status,
+    })
+}
+
+/// Process groups of running solvers, for the interrupt handler to kill
+///
+/// A fixed array of atomics, as the handler may only use async-signal-safe
+/// operations; slots hold a group id, or 0 when free.
+#[cfg(unix)]
+static LIVE_GROUPS: [std::sync::atomic::AtomicI32; 32] = [const { std::sync::atomic::AtomicI32::new(0) }; 32];
+
+/// A solver's slot in [`LIVE_GROUPS`], freed on drop
+struct LiveGroup {
+    #[cfg(unix)]
+    slot: Option<usize>,
+}
+
+impl LiveGroup {
+    #[cfg(unix)]
+    fn register(pid: u32) -> Self {
+        let slot = LIVE_GROUPS.iter().position(|slot| {
+            slot.compare_exchange(0, pid as i32, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+        });
+        if slot.is_none() {
+            warn!("Too many solvers running; process group {pid} won't be killed on interrupt");
+        }
+        Self { slot }
+    }
+
+    #[cfg(not(unix))]
+    fn register(_pid: u32) -> Self {
+        Self {}
+    }
+}
+
+impl Drop for LiveGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(slot) = self.slot {
+            LIVE_GROUPS[slot].store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Kill running solvers' groups on SIGINT and SIGTERM, then die from the signal
+///
+/// Installed once, and only for signals that still have their default action.
+#[cfg(unix)]
+fn install_interrupt_handlers() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+
+    extern "C" fn kill_groups_and_exit(signal: libc::c_int) {
+        for slot in &LIVE_GROUPS {
+            let pgid = slot.load(Ordering::SeqCst);
+            if pgid > 0 {
+                // SAFETY: kill, signal and raise are async-signal-safe
+                unsafe {
+                    libc::kill(-pgid, libc::SIGKILL);
+                }
+            }
+        }
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+
+    INSTALL.call_once(|| {
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: sigaction is plain old data, and the handler above is async-signal-safe
+            unsafe {
+                let mut current: libc::sigaction = std::mem::zeroed();
+                if libc::sigaction(signal, std::ptr::null(), &mut current) != 0
+                    || current.sa_sigaction != libc::SIG_DFL
+                {
+                    continue;
+                }
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = kill_groups_and_exit as *const () as libc::sighandler_t;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(signal, &action, std::ptr::null_mut());
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn install_interrupt_handlers() {}
+
+/// Start the process in its own group and set its address-space limit
+#[cfg(unix)]
+fn apply_process_limits(command: &mut Command, limits: &SolverLimits) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+
+    if let Some(bytes) = limits.memory_bytes {
+        let limit = libc::rlimit {
+            rlim_cur: bytes as libc::rlim_t,
+            rlim_max: bytes as libc::rlim_t,
+        };
+        // SAFETY: setrlimit is async-signal-safe and only touches the child
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_process_limits(_command: &mut Command, limits: &SolverLimits) {
+    if limits.memory_bytes.is_some() {
+        warn!("Memory limits are only supported on Unix");
+    }
+}
+
+/// Kill a process group started by [`run_limited`]
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // SAFETY: signalling a process group has no memory-safety implications
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {
+    warn!("Killing solver processes is only supported on Unix");
+}
+
+/// Output of one solver run
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverRun {
+    pub answer: Option<String>,
+    #[serde(skip)]
+    pub stdout: String,
+    #[serde(rename = "duration_ms", serialize_with = "crate::harness::serialize_millis")]
+    pub duration: Duration,
+    /// Peak resident set size in KiB (Unix only)
+    pub peak_rss_kb: Option<u64>,
+    pub status: SolverStatus,
+}
+
+/// A solver command template
+#[derive(Debug, Clone)]
+pub struct SolverCommand {
+    template: String,
+    limits: SolverLimits,
+}
+
+impl SolverCommand {
+    /// Create a solver command from a template
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            limits: SolverLimits::default(),
+        }
+    }
+
+    /// Create solver command with resource limits
+    pub fn with_limits(mut self, limits: SolverLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Limits applied to each run
+    pub fn limits(&self) -> &SolverLimits {
+        &self.limits
+    }
+
+    /// Solver command from `EC_SOLVER`, if set
+    pub fn from_env() -> Option<Self> {
+        std::env::var(SOLVER_ENV).ok().filter(|s| !s.is_empty()).map(Self::new)
+    }
+
+    /// Substitute placeholders for a part
+    pub fn render(&self, year: i32, day: i32, part: i32, input: &Path) -> String {
+        self.template
+            .replace("{year}", &year.to_string())
+            .replace("{day}", &day.to_string())
+            .replace("{part}", &part.to_string())
+            .replace("{input}", &input.display().to_string())
+    }
+
+    /// Build the process for a part, with stdin wired to the input file
+    pub fn command(&self, year: i32, day: i32, part: i32, input: &Path) -> Result<Command> {
+        let rendered = self.render(year, day, part, input);
+        debug!("Solver command: {rendered}");
+
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(rendered)
+            .env("EC_YEAR", year.to_string())
+            .env("EC_DAY", day.to_string())
+            .env("EC_PART", part.to_string())
+            .env("EC_INPUT", input)
+            .stdin(File::open(input)?)
+            .stderr(Stdio::inherit());
+
+        Ok(command)
+    }
+
+    /// Run the solver on an input file and capture its answer
+    pub fn run(&self, year: i32, day: i32, part: i32, input: &Path) -> Result<SolverRun> {
+        let command = self.command(year, day, part, input)?;
+        let output = run_limited(command, &self.limits)?;
+
+        if output.status != SolverStatus::Success {
+            warn!("Solver {}", output.status);
+        }
+
+        Ok(SolverRun {
+            answer: last_line(&output.stdout),
+            stdout: output.stdout,
+            duration: output.duration,
+            peak_rss_kb: output.peak_rss_kb,
+            status: output.status,
+        })
+    }
+}
+
+/// Wait for a child to exit without reaping it
+#[cfg(unix)]
+fn wait_for_exit(child: &mut Child) -> Result<()> {
+    // SAFETY: siginfo_t is plain old data, and waitid only writes through the pointer we pass
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            child.id() as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOWAIT,
+        )
+    };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn wait_for_exit(child: &mut Child) -> Result<()> {
+    child.wait()?;
+    Ok(())
+}
+
+/// Reap an exited child and report its peak resident set size
+#[cfg(unix)]
+fn wait_with_peak_rss(child: &mut Child) -> Result<(ExitStatus, Option<u64>)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    // SAFETY: rusage is plain old data, and wait4 only writes through the pointers we pass
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+    if pid < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    // ru_maxrss is in KiB on Linux but bytes on macOS
+    let max_rss = usage.ru_maxrss as u64;
+    let peak_rss_kb = if cfg!(target_os = "macos") { max_rss / 1024 } else { max_rss };
+
+    Ok((ExitStatus::from_raw(status), Some(peak_rss_kb)))
+}
+
+#[cfg(not(unix))]
+fn wait_with_peak_rss(child: &mut Child) -> Result<(ExitStatus, Option<u64>)> {
+    Ok((child.wait()?, None))
+}
+
+/// Last non-empty line of solver output, trimmed
+pub fn last_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_placeholders() {
+        let solver = SolverCommand::new("./solve {year} {day} {part} < {input}");
+        let rendered = solver.render(2024, 5, 2, Path::new("data/2024/inputs/5-2.txt"));
+        assert_eq!(rendered, "./solve 2024 5 2 < data/2024/inputs/5-2.txt");
+    }
+
+    #[test]
+    fn test_last_line() {
+        assert_eq!(last_line("debug\n42\n\n"), Some("42".to_string()));
+        assert_eq!(last_line("  \n"), None);
+    }
+
+    #[test]
+    fn test_run_reads_input_from_stdin() {
+        let temp = tempfile::tempdir().unwrap();
+        let input = temp.path().join("input.txt");
+        std::fs::write(&input, "1\n2\n3\n").unwrap();
+
+        let run = SolverCommand::new("wc -l | tr -d ' '; echo part $EC_PART")
+            .run(2024, 1, 3, &input)
+            .unwrap();
+        assert_eq!(run.status, SolverStatus::Success);
+        assert_eq!(run.answer, Some("part 3".to_string()));
+        assert!(run.stdout.starts_with("3\n"));
+    }
+
+    #[test]
+    fn test_timeout_kills_process_group() {
+        let limits = SolverLimits {
+            timeout: Some(Duration::from_millis(200)),
+            ..SolverLimits::default()
+        };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 5 & sleep 5; echo done");
+
+        let output = run_limited(command, &limits).unwrap();
+        assert_eq!(output.status, SolverStatus::TimedOut);
+        assert!(output.duration < Duration::from_secs(2));
+        assert!(output.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_leftover_processes_are_killed_after_exit() {
+        let limits = SolverLimits {
+            timeout: Some(Duration::from_secs(5)),
+            ..SolverLimits::default()
+        };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 5 & echo 42");
+
+        let output = run_limited(command, &limits).unwrap();
+        assert_eq!(output.status, SolverStatus::Success);
+        assert_eq!(output.stdout, "42\n");
+        assert!(output.duration < Duration::from_secs(2));
+    }
+
+    /// Runs a solver until the test below interrupts this process
+    #[test]
+    #[ignore = "run by test_interrupt_kills_solver_group"]
+    fn interrupted_solver() {
+        let Ok(pid_file) = std::env::var("EC_CLI_TEST_PID_FILE") else {
+            return;
+        };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!("sleep 30 & echo $$ $! > {pid_file}.tmp; mv {pid_file}.tmp {pid_file}; wait"));
+        let _ = run_limited(command, &SolverLimits::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interrupt_kills_solver_group() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let pid_file = temp.path().join("pids");
+        let mut parent = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "solver::tests::interrupted_solver", "--include-ignored", "--quiet"])
+            .env("EC_CLI_TEST_PID_FILE", &pid_file)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let pids = loop {
+            if let Ok(pids) = std::fs::read_to_string(&pid_file) {
+                break pids;
+            }
+            assert!(Instant::now() < deadline, "solver never started");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        unsafe {
+            libc::kill(parent.id() as libc::pid_t, libc::SIGINT);
+        }
+        assert_eq!(parent.wait().unwrap().signal(), Some(libc::SIGINT));
+
+        // Orphans may linger as zombies until init reaps them
+        let alive = |pid: &str| {
+            let out = Command::new("ps").args(["-o", "stat=", "-p", pid]).output().unwrap();
+            let stat = String::from_utf8_lossy(&out.stdout);
+            !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+        };
+        for pid in pids.split_whitespace() {
+            while alive(pid) {
+                assert!(Instant::now() < deadline, "solver process {pid} survived the interrupt");
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_cap() {
+        let limits = SolverLimits {
+            max_output_bytes: Some(16),
+            ..SolverLimits::default()
+        };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("yes");
+
+        let output = run_limited(command, &limits).unwrap();
+        assert_eq!(output.status, SolverStatus::OutputLimitExceeded);
+        assert_eq!(output.stdout.len(), 16);
+    }
+}
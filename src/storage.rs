@@ -1,15 +1,23 @@
-use log::{debug, info};
+use log::{debug, info, warn};
+use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::error::Result;
+use crate::{display, extract};
+use crate::error::{EcError, Result};
+use crate::bench::BenchRecord;
+use crate::extract::Sample;
+use crate::models::{CargoMember, PlannedWrite, QuestKeys, QuestMetadata, StoredSample, Submission, WriteAction};
 
+/// Local file layout for inputs, descriptions, samples and history
 pub struct Storage {
     base_path: PathBuf,
     description_path: Option<PathBuf>,
     input_path: Option<PathBuf>,
     sample_path: Option<PathBuf>,
     sample_answer_path: Option<PathBuf>,
+    dry_run: bool,
+    planned_writes: RefCell<Vec<PlannedWrite>>,
 }
 
 impl Storage {
@@ -22,9 +30,32 @@ impl Storage {
             input_path: None,
             sample_path: None,
             sample_answer_path: None,
+            dry_run: false,
+            planned_writes: RefCell::new(Vec::new()),
         }
     }
 
+    /// Create storage manager that records writes instead of performing them
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Base directory all default paths are resolved against
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Whether writes are only being recorded
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Writes skipped so far in dry-run mode, in order
+    pub fn planned_writes(&self) -> Vec<PlannedWrite> {
+        self.planned_writes.borrow().clone()
+    }
+
     /// Create storage manager with custom description path
     pub fn with_description_path(mut self, path: PathBuf) -> Self {
         self.description_path = Some(path);
@@ -64,6 +95,119 @@ impl Storage {
         self.base_path.join(year.to_string()).join("descriptions")
     }
 
+    /// Get the path for superseded versions of a quest's description
+    fn description_history_dir(&self, year: i32, day: i32) -> PathBuf {
+        self.base_path
+            .join(year.to_string())
+            .join("history")
+            .join(day.to_string())
+    }
+
+    /// Get the path for cached quest keys directory
+    fn keys_dir(&self, year: i32) -> PathBuf {
+        self.base_path.join(year.to_string()).join("keys")
+    }
+
+    /// Get the path for submission history directory
+    fn submissions_dir(&self, year: i32) -> PathBuf {
+        self.base_path.join(year.to_string()).join("submissions")
+    }
+
+    /// Get the path for benchmark history directory
+    fn bench_dir(&self, year: i32) -> PathBuf {
+        self.base_path.join(year.to_string()).join("bench")
+    }
+
+    /// Get the path of the generated workspace member record for a quest
+    fn cargo_member_file(&self, year: i32, day: i32) -> PathBuf {
+        self.base_path
+            .join(year.to_string())
+            .join("projects")
+            .join(format!("{day}.json"))
+    }
+
+    /// Get the path of the input file for a part
+    pub fn input_file(&self, year: i32, day: i32, part: i32) -> PathBuf {
+        self.input_path
+            .clone()
+            .unwrap_or_else(|| self.inputs_dir(year).join(format!("{day}-{part}.txt")))
+    }
+
+    /// Get the path of the `n`th sample file for a part (numbered from 1)
+    pub fn sample_file(&self, year: i32, day: i32, part: i32, n: usize) -> PathBuf {
+        self.sample_path
+            .clone()
+            .unwrap_or_else(|| self.numbered_sample_file(year, day, part, n, "txt"))
+    }
+
+    /// Get the path of the expected answer file for the `n`th sample of a part
+    pub fn expected_answer_file(&self, year: i32, day: i32, part: i32, n: usize) -> PathBuf {
+        self.sample_answer_path
+            .clone()
+            .unwrap_or_else(|| self.numbered_sample_file(year, day, part, n, "answer"))
+    }
+
+    /// `{day}-{part}.{n}.{ext}`, or the unnumbered file older versions wrote
+    /// when that is all there is for sample 1
+    fn numbered_sample_file(&self, year: i32, day: i32, part: i32, n: usize, ext: &str) -> PathBuf {
+        let numbered = self.samples_dir(year).join(format!("{day}-{part}.{n}.{ext}"));
+        let legacy = self.legacy_sample_file(year, day, part, ext);
+
+        if n == 1 && !numbered.exists() && legacy.exists() {
+            legacy
+        } else {
+            numbered
+        }
+    }
+
+    /// The unnumbered `{day}-{part}.{ext}` file older versions wrote
+    fn legacy_sample_file(&self, year: i32, day: i32, part: i32, ext: &str) -> PathBuf {
+        self.samples_dir(year).join(format!("{day}-{part}.{ext}"))
+    }
+
+    /// Move an unnumbered sample and answer to `.1.`, so new saves never mix
+    /// the old and numbered layouts
+    fn migrate_legacy_samples(&self, year: i32, day: i32, part: i32) -> Result<()> {
+        for ext in ["txt", "answer"] {
+            let legacy = self.legacy_sample_file(year, day, part, ext);
+            if !legacy.exists() {
+                continue;
+            }
+            let numbered = self.samples_dir(year).join(format!("{day}-{part}.1.{ext}"));
+            if !numbered.exists() {
+                info!("Renaming {legacy:?} to {numbered:?}");
+                self.write(&numbered, &fs::read_to_string(&legacy)?)?;
+            }
+            self.remove(&legacy)?;
+        }
+        Ok(())
+    }
+
+    /// Get the path of the description file for a quest
+    pub fn description_file(&self, year: i32, day: i32) -> PathBuf {
+        self.description_path
+            .clone()
+            .unwrap_or_else(|| self.descriptions_dir(year).join(format!("{day}.html")))
+    }
+
+    /// Get the path of the cached quest keys file
+    fn keys_file(&self, year: i32, day: i32) -> PathBuf {
+        self.keys_dir(year).join(format!("{day}.json"))
+    }
+
+    /// Get the path of the metadata file for a quest
+    fn metadata_file(&self, year: i32, day: i32) -> PathBuf {
+        self.base_path
+            .join(year.to_string())
+            .join("metadata")
+            .join(format!("{day}.json"))
+    }
+
+    /// Get the path of the submission history file for a quest
+    fn submissions_file(&self, year: i32, day: i32) -> PathBuf {
+        self.submissions_dir(year).join(format!("{day}.json"))
+    }
+
     /// Ensure directory exists
     fn ensure_dir<P: AsRef<Path>>(path: P) -> Result<()> {
         let path = path.as_ref();
@@ -74,50 +218,90 @@ impl Storage {
         Ok(())
     }
 
-    /// Save puzzle input to file
-    pub fn save_input(&self, year: i32, day: i32, part: i32, content: &str) -> Result<PathBuf> {
-        let path = if let Some(custom_path) = &self.input_path {
-            // Use custom path directly
-            custom_path.clone()
-        } else {
-            // Use default path structure
-            let dir = self.inputs_dir(year);
-            Self::ensure_dir(&dir)?;
-            let filename = format!("{day}-{part}.txt");
-            dir.join(filename)
-        };
+    /// Write a file, creating parent directories as needed
+    ///
+    /// In dry-run mode nothing touches the disk; the write is recorded along
+    /// with a diff against the current content instead.
+    fn write(&self, path: &Path, content: &str) -> Result<()> {
+        self.write_bytes(path, content.as_bytes())
+    }
+
+    /// Like `write`, for any content; only text gets a dry-run diff
+    fn write_bytes(&self, path: &Path, content: &[u8]) -> Result<()> {
+        if self.dry_run {
+            let existing = fs::read(path).ok();
+            let action = match &existing {
+                None => WriteAction::Create,
+                Some(old) if old == content => WriteAction::Unchanged,
+                Some(_) => WriteAction::Overwrite,
+            };
+            let diff = existing
+                .filter(|_| action == WriteAction::Overwrite)
+                .and_then(|old| {
+                    let old = String::from_utf8(old).ok()?;
+                    let new = std::str::from_utf8(content).ok()?;
+                    Some(
+                        similar::TextDiff::from_lines(old.as_str(), new)
+                            .unified_diff()
+                            .header("current", "new")
+                            .to_string(),
+                    )
+                });
+
+            info!("Dry run: would {} {path:?}", action.verb());
+            self.planned_writes.borrow_mut().push(PlannedWrite {
+                path: path.to_path_buf(),
+                action,
+                diff,
+            });
+            return Ok(());
+        }
 
-        // Ensure parent directory exists for custom paths
+        // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             Self::ensure_dir(parent)?;
         }
 
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Write a file outside the data layout, such as generated source code
+    ///
+    /// Honours dry-run like every other save
+    pub fn save_file(&self, path: &Path, content: &str) -> Result<()> {
+        debug!("Saving file {path:?}");
+        self.write(path, content)
+    }
+
+    /// Write a binary file outside the data layout, such as a downloaded image
+    pub fn save_binary(&self, path: &Path, content: &[u8]) -> Result<()> {
+        debug!("Saving {} bytes to {path:?}", content.len());
+        self.write_bytes(path, content)
+    }
+
+    /// Save puzzle input to file
+    pub fn save_input(&self, year: i32, day: i32, part: i32, content: &str) -> Result<PathBuf> {
+        let path = self.input_file(year, day, part);
+
         info!("Saving input to {path:?}");
-        fs::write(&path, content)?;
+        self.write(&path, content)?;
 
         Ok(path)
     }
 
-    /// Save sample/example data to file
-    pub fn save_sample(&self, year: i32, day: i32, part: i32, content: &str) -> Result<PathBuf> {
-        let path = if let Some(custom_path) = &self.sample_path {
-            // Use custom path directly
-            custom_path.clone()
-        } else {
-            // Use default path structure
-            let dir = self.samples_dir(year);
-            Self::ensure_dir(&dir)?;
-            let filename = format!("{day}-{part}.txt");
-            dir.join(filename)
+    /// Save the `n`th sample of a part to file
+    pub fn save_sample(&self, year: i32, day: i32, part: i32, n: usize, content: &str) -> Result<PathBuf> {
+        let path = match &self.sample_path {
+            Some(path) => path.clone(),
+            None => {
+                self.migrate_legacy_samples(year, day, part)?;
+                self.samples_dir(year).join(format!("{day}-{part}.{n}.txt"))
+            }
         };
 
-        // Ensure parent directory exists for custom paths
-        if let Some(parent) = path.parent() {
-            Self::ensure_dir(parent)?;
-        }
-
         info!("Saving sample to {path:?}");
-        fs::write(&path, content)?;
+        self.write(&path, content)?;
 
         Ok(path)
     }
@@ -128,59 +312,201 @@ impl Storage {
         year: i32,
         day: i32,
         part: i32,
+        n: usize,
         content: &str,
     ) -> Result<PathBuf> {
-        let path = if let Some(custom_path) = &self.sample_answer_path {
-            // Use custom path directly
-            custom_path.clone()
-        } else {
-            // Use default path structure
-            let dir = self.samples_dir(year);
-            Self::ensure_dir(&dir)?;
-            let filename = format!("{day}-{part}.answer");
-            dir.join(filename)
+        let path = match &self.sample_answer_path {
+            Some(path) => path.clone(),
+            None => {
+                self.migrate_legacy_samples(year, day, part)?;
+                self.samples_dir(year).join(format!("{day}-{part}.{n}.answer"))
+            }
         };
 
-        // Ensure parent directory exists for custom paths
-        if let Some(parent) = path.parent() {
-            Self::ensure_dir(parent)?;
-        }
-
         info!("Saving expected answer to {path:?}");
-        fs::write(&path, content)?;
+        self.write(&path, content)?;
 
         Ok(path)
     }
 
+    /// Samples of every part of a description
+    ///
+    /// Parts with hand-picked samples in the quest metadata use those picks;
+    /// the rest are extracted automatically
+    pub fn description_samples(&self, year: i32, day: i32, description: &str) -> Result<Vec<Sample>> {
+        let metadata = self.load_metadata(year, day)?;
+        let mut samples = Vec::new();
+
+        for (part_num, part_html) in (1..).zip(display::split_parts(description)) {
+            match metadata.sample_picks.get(&part_num) {
+                Some(picks) => {
+                    debug!("Using hand-picked samples for part {part_num}");
+                    samples.extend(extract::apply_picks(part_num, part_html, picks)?);
+                }
+                None => samples.extend(extract::extract_part_samples(part_num, part_html)),
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// Extract every sample of each part and save it with its expected answer
+    ///
+    /// Samples whose answer couldn't be paired are still saved, without an
+    /// `.answer` file, and reported. With a custom sample path only the last
+    /// sample of the part is kept. Returns the paths of every file written
+    pub fn save_samples(
+        &self,
+        year: i32,
+        day: i32,
+        description: &str,
+        only_part: Option<i32>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        let samples = self.description_samples(year, day, description)?;
+
+        for part_num in 1..=3 {
+            if only_part.is_some_and(|p| p != part_num) {
+                continue;
+            }
+
+            let mut part_samples: Vec<Sample> =
+                samples.iter().filter(|s| s.part == part_num).cloned().collect();
+            if self.sample_path.is_some() {
+                part_samples = part_samples.pop().into_iter().collect();
+            }
+            written.extend(self.save_part_samples(year, day, part_num, &part_samples)?);
+        }
+
+        Ok(written)
+    }
+
+    /// Save a part's samples as `{day}-{part}.{n}.txt` / `.answer`, numbered in order
+    pub fn save_part_samples(
+        &self,
+        year: i32,
+        day: i32,
+        part: i32,
+        samples: &[Sample],
+    ) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        self.remove_samples_after(year, day, part, samples.len())?;
+
+        for (n, sample) in (1..).zip(samples) {
+            let path = self.save_sample(year, day, part, n, &sample.input)?;
+            info!("Sample {n} for part {part} saved to {path:?}");
+            written.push(path);
+
+            if let Some(answer) = &sample.expected_answer {
+                let answer_path = self.save_expected_answer(year, day, part, n, answer)?;
+                info!("Expected answer for part {part} sample {n} saved to {answer_path:?}");
+                written.push(answer_path);
+            } else {
+                // Don't leave the answer of an earlier sample with this number behind
+                if self.sample_answer_path.is_none() {
+                    self.remove(&self.expected_answer_file(year, day, part, n))?;
+                }
+                warn!(
+                    "Could not pair sample {n} of part {part} with an expected answer{}",
+                    sample
+                        .context_text
+                        .as_ref()
+                        .map(|c| format!(" (after \"{c}\")"))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Delete stored samples of a part numbered above `keep`
+    ///
+    /// Keeps an earlier, longer set of samples from lingering after a shorter one is saved
+    fn remove_samples_after(&self, year: i32, day: i32, part: i32, keep: usize) -> Result<()> {
+        if self.sample_path.is_some() {
+            return Ok(());
+        }
+
+        for n in self.sample_numbers(year, day, part).into_iter().filter(|&n| n > keep) {
+            self.remove(&self.sample_file(year, day, part, n))?;
+            self.remove(&self.expected_answer_file(year, day, part, n))?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a stale file if it exists, honouring dry-run
+    fn remove(&self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        if self.dry_run {
+            info!("Dry run: would remove {path:?}");
+            self.planned_writes.borrow_mut().push(PlannedWrite {
+                path: path.to_path_buf(),
+                action: WriteAction::Remove,
+                diff: None,
+            });
+        } else {
+            info!("Removing stale file {path:?}");
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
     /// Save puzzle description to file
     pub fn save_description(&self, year: i32, day: i32, content: &str) -> Result<PathBuf> {
-        let path = if let Some(custom_path) = &self.description_path {
-            // Use custom path directly
-            custom_path.clone()
-        } else {
-            // Use default path structure
-            let dir = self.descriptions_dir(year);
-            Self::ensure_dir(&dir)?;
-            let filename = format!("{day}.html");
-            dir.join(filename)
-        };
+        let path = self.description_file(year, day);
 
-        // Ensure parent directory exists for custom paths
-        if let Some(parent) = path.parent() {
-            Self::ensure_dir(parent)?;
+        // Keep the version being replaced, so `read --new` can tell what changed
+        if let Ok(previous) = fs::read_to_string(&path) {
+            if previous != content {
+                let version = self.description_versions(year, day).last().map_or(1, |v| v + 1);
+                let kept = self.description_history_dir(year, day).join(format!("{version}.html"));
+                info!("Keeping the previous description as {kept:?}");
+                self.write(&kept, &previous)?;
+            }
         }
 
         info!("Saving description to {path:?}");
-        fs::write(&path, content)?;
+        self.write(&path, content)?;
 
         Ok(path)
     }
 
+    /// Numbers of the superseded description versions kept for a quest, oldest first
+    pub fn description_versions(&self, year: i32, day: i32) -> Vec<usize> {
+        let Ok(entries) = fs::read_dir(self.description_history_dir(year, day)) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<usize> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "html").then_some(())?;
+                path.file_stem()?.to_str()?.parse().ok()
+            })
+            .collect();
+        versions.sort_unstable();
+        versions
+    }
+
+    /// The description as it was before the last change, if one was kept
+    pub fn previous_description(&self, year: i32, day: i32) -> Result<Option<String>> {
+        let Some(version) = self.description_versions(year, day).pop() else {
+            return Ok(None);
+        };
+        let path = self.description_history_dir(year, day).join(format!("{version}.html"));
+
+        debug!("Loading previous description from {path:?}");
+        Ok(Some(fs::read_to_string(&path)?))
+    }
+
     /// Load puzzle description from file
     pub fn load_description(&self, year: i32, day: i32) -> Result<String> {
-        let dir = self.descriptions_dir(year);
-        let filename = format!("{day}.html");
-        let path = dir.join(filename);
+        let path = self.description_file(year, day);
 
         debug!("Loading description from {path:?}");
         let content = fs::read_to_string(&path)?;
@@ -190,9 +516,398 @@ impl Storage {
 
     /// Check if description file exists
     pub fn has_description(&self, year: i32, day: i32) -> bool {
-        let dir = self.descriptions_dir(year);
-        let filename = format!("{day}.html");
-        let path = dir.join(filename);
-        path.exists()
+        self.description_file(year, day).exists()
+    }
+
+    /// Check if input file exists for a part
+    pub fn has_input(&self, year: i32, day: i32, part: i32) -> bool {
+        self.input_file(year, day, part).exists()
+    }
+
+    /// Check if any sample is stored for a part
+    pub fn has_sample(&self, year: i32, day: i32, part: i32) -> bool {
+        !self.sample_numbers(year, day, part).is_empty()
+    }
+
+    /// Numbers of the samples stored for a part, in order
+    pub fn sample_numbers(&self, year: i32, day: i32, part: i32) -> Vec<usize> {
+        if self.sample_path.is_some() || !self.samples_dir(year).is_dir() {
+            return if self.sample_file(year, day, part, 1).exists() { vec![1] } else { Vec::new() };
+        }
+
+        let prefix = format!("{day}-{part}.");
+        let mut numbers: Vec<usize> = fs::read_dir(self.samples_dir(year))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_prefix(&prefix)?.strip_suffix(".txt")?.parse().ok()
+            })
+            .collect();
+        // An unnumbered file stands in for sample 1 until a `.1.` file exists
+        if !numbers.contains(&1) && self.legacy_sample_file(year, day, part, "txt").exists() {
+            numbers.push(1);
+        }
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    }
+
+    /// Every stored sample of a part with its expected answer, if paired
+    pub fn stored_samples(&self, year: i32, day: i32, part: i32) -> Result<Vec<StoredSample>> {
+        self.sample_numbers(year, day, part)
+            .into_iter()
+            .map(|number| {
+                Ok(StoredSample {
+                    number,
+                    path: self.sample_file(year, day, part, number),
+                    expected_answer: self.load_expected_answer(year, day, part, number)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Load puzzle input for a part
+    pub fn load_input(&self, year: i32, day: i32, part: i32) -> Result<String> {
+        let path = self.input_file(year, day, part);
+        Self::load_required(&path, "input")
+    }
+
+    /// Load the `n`th sample input of a part
+    pub fn load_sample(&self, year: i32, day: i32, part: i32, n: usize) -> Result<String> {
+        let path = self.sample_file(year, day, part, n);
+        Self::load_required(&path, "sample")
+    }
+
+    /// Read a file that is expected to exist, naming it in the error otherwise
+    fn load_required(path: &Path, what: &str) -> Result<String> {
+        if !path.exists() {
+            return Err(EcError::MissingData {
+                what: what.to_string(),
+                path: path.to_path_buf(),
+            });
+        }
+
+        debug!("Loading {what} from {path:?}");
+        Ok(fs::read_to_string(path)?)
+    }
+
+    /// Load the expected answer of the `n`th sample of a part, if one was paired
+    pub fn load_expected_answer(&self, year: i32, day: i32, part: i32, n: usize) -> Result<Option<String>> {
+        let path = self.expected_answer_file(year, day, part, n);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        debug!("Loading expected answer from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(Some(content.trim().to_string()))
+    }
+
+    /// Cache quest keys so later commands know which parts are unlocked
+    pub fn save_keys(&self, year: i32, day: i32, keys: &QuestKeys) -> Result<PathBuf> {
+        let path = self.keys_file(year, day);
+
+        debug!("Caching quest keys to {path:?}");
+        self.write(&path, &serde_json::to_string_pretty(keys)?)?;
+
+        Ok(path)
+    }
+
+    /// Load cached quest keys, if any were saved
+    pub fn load_keys(&self, year: i32, day: i32) -> Result<Option<QuestKeys>> {
+        let path = self.keys_file(year, day);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        debug!("Loading cached quest keys from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Remember where `new --cargo` generated a quest so its tests can be updated
+    pub fn record_cargo_member(&self, year: i32, day: i32, member: &CargoMember) -> Result<PathBuf> {
+        let path = self.cargo_member_file(year, day);
+
+        debug!("Recording workspace member to {path:?}");
+        self.write(&path, &serde_json::to_string_pretty(member)?)?;
+
+        Ok(path)
+    }
+
+    /// Load the quest's generated workspace member, if any
+    pub fn load_cargo_member(&self, year: i32, day: i32) -> Result<Option<CargoMember>> {
+        let path = self.cargo_member_file(year, day);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        debug!("Loading workspace member record from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Save choices for a quest that later fetches must respect
+    pub fn save_metadata(&self, year: i32, day: i32, metadata: &QuestMetadata) -> Result<PathBuf> {
+        let path = self.metadata_file(year, day);
+
+        debug!("Saving quest metadata to {path:?}");
+        self.write(&path, &serde_json::to_string_pretty(metadata)?)?;
+
+        Ok(path)
+    }
+
+    /// Load the quest's metadata, empty if none was saved
+    pub fn load_metadata(&self, year: i32, day: i32) -> Result<QuestMetadata> {
+        let path = self.metadata_file(year, day);
+        if !path.exists() {
+            return Ok(QuestMetadata::default());
+        }
+
+        debug!("Loading quest metadata from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Append a submission to the quest's local history
+    pub fn record_submission(&self, year: i32, day: i32, submission: &Submission) -> Result<PathBuf> {
+        let mut submissions = self.load_submissions(year, day)?;
+        submissions.push(submission.clone());
+
+        let path = self.submissions_file(year, day);
+
+        debug!("Recording submission to {path:?}");
+        self.write(&path, &serde_json::to_string_pretty(&submissions)?)?;
+
+        Ok(path)
+    }
+
+    /// Append a benchmark result to the part's history
+    pub fn record_bench(&self, year: i32, day: i32, part: i32, record: &BenchRecord) -> Result<PathBuf> {
+        let mut history = self.load_bench_history(year, day, part)?;
+        history.push(record.clone());
+
+        let path = self.bench_dir(year).join(format!("{day}-{part}.json"));
+
+        debug!("Recording benchmark to {path:?}");
+        self.write(&path, &serde_json::to_string_pretty(&history)?)?;
+
+        Ok(path)
+    }
+
+    /// Load the part's benchmark history (oldest first)
+    pub fn load_bench_history(&self, year: i32, day: i32, part: i32) -> Result<Vec<BenchRecord>> {
+        let path = self.bench_dir(year).join(format!("{day}-{part}.json"));
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        debug!("Loading benchmark history from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Every (year, day) with a stored submission history, in order
+    pub fn quests_with_submissions(&self) -> Result<Vec<(i32, i32)>> {
+        self.quests_in(|year| self.submissions_dir(year))
+    }
+
+    /// Every (year, day) with a stored description, in order
+    pub fn quests_with_descriptions(&self) -> Result<Vec<(i32, i32)>> {
+        self.quests_in(|year| self.descriptions_dir(year))
+    }
+
+    /// Every (year, day) with a `{day}.*` file in a per-year directory
+    fn quests_in(&self, dir: impl Fn(i32) -> PathBuf) -> Result<Vec<(i32, i32)>> {
+        let mut quests = Vec::new();
+        if !self.base_path.is_dir() {
+            return Ok(quests);
+        }
+
+        for year_entry in fs::read_dir(&self.base_path)? {
+            let Some(year) = year_entry?.file_name().to_str().and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            let dir = dir(year);
+            if !dir.is_dir() {
+                continue;
+            }
+            for day_entry in fs::read_dir(dir)? {
+                let path = day_entry?.path();
+                if let Some(day) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) {
+                    quests.push((year, day));
+                }
+            }
+        }
+
+        quests.sort_unstable();
+        quests.dedup();
+        Ok(quests)
+    }
+
+    /// Load the quest's submission history (oldest first)
+    pub fn load_submissions(&self, year: i32, day: i32) -> Result<Vec<Submission>> {
+        let path = self.submissions_file(year, day);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        debug!("Loading submission history from {path:?}");
+        let content = fs::read_to_string(&path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_records_writes_without_touching_disk() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let existing = Storage::new(Some(base.clone()));
+        existing.save_input(2024, 1, 1, "old\n").unwrap();
+
+        let storage = Storage::new(Some(base.clone())).with_dry_run(true);
+        storage.save_input(2024, 1, 1, "new\n").unwrap();
+        storage.save_input(2024, 1, 2, "fresh\n").unwrap();
+
+        let planned = storage.planned_writes();
+        assert_eq!(planned[0].action, WriteAction::Overwrite);
+        assert!(planned[0].diff.as_ref().unwrap().contains("-old\n+new"));
+        assert_eq!(planned[1].action, WriteAction::Create);
+        assert_eq!(fs::read_to_string(storage.input_file(2024, 1, 1)).unwrap(), "old\n");
+        assert!(!storage.has_input(2024, 1, 2));
+
+        let two = r#"<pre class="note">1</pre><pre><b>1</b></pre><pre class="note">2</pre><pre><b>2</b></pre>"#;
+        existing.save_samples(2024, 1, two, None).unwrap();
+        storage.save_samples(2024, 1, r#"<pre class="note">1</pre><pre><b>1</b></pre>"#, None).unwrap();
+        let removed: Vec<_> = storage
+            .planned_writes()
+            .into_iter()
+            .filter(|write| write.action == WriteAction::Remove)
+            .map(|write| write.path)
+            .collect();
+        assert_eq!(removed, vec![storage.sample_file(2024, 1, 1, 2), storage.expected_answer_file(2024, 1, 1, 2)]);
+        assert!(storage.sample_file(2024, 1, 1, 2).exists());
+    }
+
+    #[test]
+    fn test_save_samples_numbers_every_sample() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        let description = r#"<pre class="note">1 2</pre><pre><b>3</b></pre>
+<pre class="note">unpaired</pre>
+<pre class="note">4 5</pre><pre><b>9</b></pre>"#;
+
+        storage.save_samples(2024, 1, description, None).unwrap();
+
+        let samples = storage.stored_samples(2024, 1, 1).unwrap();
+        let answers: Vec<_> = samples.iter().map(|s| (s.number, s.expected_answer.as_deref())).collect();
+        assert_eq!(answers, vec![(1, Some("3")), (2, None), (3, Some("9"))]);
+        assert_eq!(storage.load_sample(2024, 1, 1, 3).unwrap(), "4 5");
+        assert!(samples[0].path.ends_with("1-1.1.txt"));
+    }
+
+    #[test]
+    fn test_picked_samples_survive_refetch() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        let description = r#"<pre class="note">guess</pre><pre>real</pre><pre><b>7</b></pre>"#;
+
+        let mut metadata = QuestMetadata::default();
+        metadata.sample_picks.insert(1, vec!["2:3".parse().unwrap()]);
+        storage.save_metadata(2024, 1, &metadata).unwrap();
+        storage.save_samples(2024, 1, description, None).unwrap();
+
+        storage.save_sample(2024, 1, 1, 2, "stale").unwrap();
+        storage.save_samples(2024, 1, description, None).unwrap();
+
+        assert_eq!(storage.sample_numbers(2024, 1, 1), vec![1]);
+        assert_eq!(storage.load_sample(2024, 1, 1, 1).unwrap(), "real\n");
+        assert_eq!(storage.load_expected_answer(2024, 1, 1, 1).unwrap().as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn test_replaced_descriptions_are_kept() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+
+        storage.save_description(2024, 1, "one").unwrap();
+        assert_eq!(storage.previous_description(2024, 1).unwrap(), None);
+
+        storage.save_description(2024, 1, "one two").unwrap();
+        storage.save_description(2024, 1, "one two").unwrap();
+        storage.save_description(2024, 1, "one two three").unwrap();
+
+        assert_eq!(storage.description_versions(2024, 1), vec![1, 2]);
+        assert_eq!(storage.previous_description(2024, 1).unwrap().as_deref(), Some("one two"));
+        assert_eq!(storage.quests_with_descriptions().unwrap(), vec![(2024, 1)]);
+    }
+
+    #[test]
+    fn test_unnumbered_sample_is_read_as_sample_one() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        let dir = base.join("2024").join("samples");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1-2.txt"), "old").unwrap();
+        fs::write(dir.join("1-2.answer"), "42\n").unwrap();
+
+        assert_eq!(storage.sample_numbers(2024, 1, 2), vec![1]);
+        assert_eq!(storage.load_expected_answer(2024, 1, 2, 1).unwrap().as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_legacy_sample_next_to_numbered_ones() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let storage = Storage::new(Some(base.clone()));
+        let dir = base.join("2024").join("samples");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1-1.txt"), "old").unwrap();
+        fs::write(dir.join("1-1.answer"), "42\n").unwrap();
+        fs::write(dir.join("1-1.2.txt"), "second").unwrap();
+
+        // Reading a mixed directory keeps the unnumbered pair as sample 1
+        assert_eq!(storage.sample_numbers(2024, 1, 1), vec![1, 2]);
+        assert_eq!(storage.load_sample(2024, 1, 1, 1).unwrap(), "old");
+
+        // Saving moves everything to numbered files
+        let description = r#"<pre class="note">a</pre><pre><b>1</b></pre><pre class="note">b</pre><pre><b>2</b></pre>"#;
+        storage.save_samples(2024, 1, description, None).unwrap();
+
+        assert_eq!(storage.sample_numbers(2024, 1, 1), vec![1, 2]);
+        assert!(!dir.join("1-1.txt").exists());
+        assert!(!dir.join("1-1.answer").exists());
+        assert_eq!(fs::read_to_string(dir.join("1-1.1.txt")).unwrap(), "a");
+        assert_eq!(storage.load_expected_answer(2024, 1, 1, 1).unwrap().as_deref(), Some("1"));
+    }
+    #[test]
+    fn test_leftover_legacy_sample_is_not_counted_twice() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        let dir = temp.path().join("2024").join("samples");
+        fs::create_dir_all(&dir).unwrap();
+        // Whatever order read_dir lists these in, sample 1 is reported once
+        for name in ["1-1.2.txt", "1-1.1.txt", "1-1.10.txt", "1-1.txt"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+
+        assert_eq!(storage.sample_numbers(2024, 1, 1), vec![1, 2, 10]);
+        assert_eq!(storage.load_sample(2024, 1, 1, 1).unwrap(), "1-1.1.txt");
     }
 }
@@ -0,0 +1,258 @@
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::error::Result;
+use crate::solver::{SolverCommand, SolverRun, SolverStatus};
+use crate::storage::Storage;
+
+/// Polls files matching glob patterns for modifications
+///
+/// Polling keeps this portable and dependency-free; source trees are small
+/// enough that checking mtimes a few times a second is cheap.
+pub struct FileWatcher {
+    patterns: Vec<String>,
+    snapshot: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl FileWatcher {
+    /// Start watching the given glob patterns (e.g. `src/**/*.rs`)
+    pub fn new(patterns: Vec<String>) -> Self {
+        let mut watcher = Self {
+            patterns,
+            snapshot: BTreeMap::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    /// Number of files currently matched
+    pub fn file_count(&self) -> usize {
+        self.snapshot.len()
+    }
+
+    /// Modification times of every matching file
+    fn scan(&self) -> BTreeMap<PathBuf, SystemTime> {
+        let mut files = BTreeMap::new();
+
+        for pattern in &self.patterns {
+            let paths = match glob::glob(pattern) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    warn!("Invalid watch pattern {pattern:?}: {e}");
+                    continue;
+                }
+            };
+            for path in paths.flatten() {
+                if let Ok(modified) = path.metadata().and_then(|m| m.modified()) {
+                    files.insert(path, modified);
+                }
+            }
+        }
+
+        files
+    }
+
+    /// Check for added, removed or modified files since the last call
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let current = self.scan();
+
+        let mut changed: Vec<PathBuf> = current
+            .iter()
+            .filter(|(path, modified)| self.snapshot.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.snapshot
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        );
+
+        self.snapshot = current;
+        changed
+    }
+
+    /// Block until files change, then wait for saves to settle
+    ///
+    /// Returns every file changed during the debounce window.
+    pub fn wait_for_change(&mut self, poll: Duration, debounce: Duration) -> Vec<PathBuf> {
+        let mut changed = loop {
+            std::thread::sleep(poll);
+            let changed = self.changed();
+            if !changed.is_empty() {
+                break changed;
+            }
+        };
+
+        // Editors often write several times per save; wait for a quiet period
+        loop {
+            std::thread::sleep(debounce);
+            let more = self.changed();
+            if more.is_empty() {
+                break;
+            }
+            changed.extend(more);
+        }
+
+        changed.sort();
+        changed.dedup();
+        debug!("Changed files: {changed:?}");
+        changed
+    }
+}
+
+/// One solver run made during a watch round
+#[derive(Debug)]
+pub struct Check {
+    pub label: String,
+    /// None when the data file isn't stored
+    pub run: Option<SolverRun>,
+    pub expected: Option<String>,
+}
+
+impl Check {
+    /// Whether the answer matched, or None when there was nothing to compare
+    pub fn passed(&self) -> Option<bool> {
+        let run = self.run.as_ref()?;
+        if run.status != SolverStatus::Success {
+            return Some(false);
+        }
+        self.expected.as_deref().map(|e| run.answer.as_deref() == Some(e))
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = &self.label;
+        let Some(run) = &self.run else {
+            return write!(f, "{label}: not stored");
+        };
+
+        let millis = run.duration.as_secs_f64() * 1000.0;
+        let answer = run.answer.as_deref().unwrap_or("(no output)");
+        if run.status != SolverStatus::Success {
+            return write!(f, "{label}: ✗ solver {} ({millis:.1}ms)", run.status);
+        }
+
+        match (self.passed(), &self.expected) {
+            (Some(true), _) => write!(f, "{label}: ✓ {answer} ({millis:.1}ms)"),
+            (Some(false), Some(expected)) => {
+                write!(f, "{label}: ✗ {answer}, expected {expected} ({millis:.1}ms)")
+            }
+            _ => write!(f, "{label}: {answer} ({millis:.1}ms, nothing to compare against)"),
+        }
+    }
+}
+
+/// Run the solver on a part's samples, then on its input if they all pass
+///
+/// `sample` limits the round to one sample. Each check is reported as soon as
+/// it finishes, so slow solvers still show progress.
+pub fn check_round(
+    storage: &Storage,
+    solver: &SolverCommand,
+    year: i32,
+    day: i32,
+    part: i32,
+    sample: Option<usize>,
+    mut report: impl FnMut(&Check),
+) -> Result<()> {
+    let mut check = |label: String, path: Option<PathBuf>, expected: Option<String>| -> Result<Option<bool>> {
+        let run = match path {
+            Some(path) => Some(solver.run(year, day, part, &path)?),
+            None => None,
+        };
+        let check = Check { label, run, expected };
+        report(&check);
+        Ok(check.passed())
+    };
+
+    let samples: Vec<_> = storage
+        .stored_samples(year, day, part)?
+        .into_iter()
+        .filter(|s| sample.is_none_or(|n| n == s.number))
+        .collect();
+    if samples.is_empty() {
+        check("Sample".to_string(), None, None)?;
+    }
+    let mut samples_passed = true;
+    for stored in samples {
+        let passed = check(format!("Sample {}", stored.number), Some(stored.path), stored.expected_answer)?;
+        samples_passed &= passed != Some(false);
+    }
+
+    // Only spend time on the real input once the samples are right
+    if samples_passed {
+        let accepted = storage
+            .load_submissions(year, day)?
+            .into_iter()
+            .rfind(|s| s.part == part && s.correct)
+            .map(|s| s.answer);
+        let input = storage.has_input(year, day, part).then(|| storage.input_file(year, day, part));
+        check("Input".to_string(), input, accepted)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(storage: &Storage, solver: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        check_round(storage, &SolverCommand::new(solver), 2024, 1, 1, None, |check| {
+            // Drop the timing, which varies between runs
+            let line = check.to_string();
+            lines.push(line.split(" (").next().unwrap().to_string());
+        })
+        .unwrap();
+        lines
+    }
+
+    #[test]
+    fn test_check_round_runs_input_after_passing_samples() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Storage::new(Some(temp.path().to_path_buf()));
+        assert_eq!(round(&storage, "cat"), ["Sample: not stored", "Input: not stored"]);
+
+        storage.save_samples(2024, 1, r#"<pre class="note">7</pre><pre><b>7</b></pre>"#, None).unwrap();
+        storage.save_input(2024, 1, 1, "42\n").unwrap();
+        assert_eq!(round(&storage, "cat"), ["Sample 1: ✓ 7", "Input: 42"]);
+        assert_eq!(round(&storage, "echo 8"), ["Sample 1: ✗ 8, expected 7"]);
+        assert_eq!(round(&storage, "exit 1"), ["Sample 1: ✗ solver exited with code 1"]);
+    }
+
+    #[test]
+    fn test_detects_added_modified_and_removed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let file = dir.join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let pattern = format!("{}/*.rs", dir.display());
+        let mut watcher = FileWatcher::new(vec![pattern]);
+        assert_eq!(watcher.file_count(), 1);
+        assert!(watcher.changed().is_empty());
+
+        let added = dir.join("lib.rs");
+        std::fs::write(&added, "").unwrap();
+        assert_eq!(watcher.changed(), vec![added.clone()]);
+
+        // Set the time explicitly, as a quick rewrite can keep the same mtime
+        let later = std::fs::metadata(&file).unwrap().modified().unwrap() + Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .and_then(|f| f.set_modified(later))
+            .unwrap();
+        assert_eq!(watcher.changed(), vec![file.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        std::fs::remove_file(&added).unwrap();
+        assert_eq!(watcher.changed(), vec![added]);
+    }
+}
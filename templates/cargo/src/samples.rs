@@ -0,0 +1,10 @@
+//! Sample tests for quest {{day}}, generated by `ec-cli new --cargo`
+//!
+//! `ec-cli fetch` rewrites this file when new parts unlock; do not edit it.
+{{#samples}}
+#[test]
+fn sample_part{{part}}_{{index}}() {
+    let input = {{sample_rs}};
+    assert_eq!(super::solve({{part}}, input), {{expected_rs}});
+}
+{{/samples}}
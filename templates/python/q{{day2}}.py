@@ -0,0 +1,30 @@
+"""Everybody Codes {{year}}, quest {{day}}: {{title}}"""
+
+import os
+import sys
+import unittest
+
+INPUTS = [{{input1_py}}, {{input2_py}}, {{input3_py}}]
+
+
+def solve(part, data):
+    raise NotImplementedError(f"part {part}")
+
+
+class SampleTests(unittest.TestCase):
+{{#samples}}
+    def test_sample_part{{part}}_{{index}}(self):
+        data = {{sample_py}}
+        self.assertEqual(solve({{part}}, data), {{expected_py}})
+{{/samples}}
+    pass
+
+
+if __name__ == "__main__":
+    if len(sys.argv) > 1 and sys.argv[1] == "test":
+        unittest.main(argv=sys.argv[:1])
+    else:
+        part = int(sys.argv[1]) if len(sys.argv) > 1 else 1
+        path = os.environ.get("EC_INPUT", INPUTS[part - 1])
+        with open(path) as f:
+            print(solve(part, f.read()))
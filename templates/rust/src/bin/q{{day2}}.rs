@@ -0,0 +1,35 @@
+//! Everybody Codes {{year}}, quest {{day}}: {{title}}
+
+const INPUTS: [&str; 3] = [{{input1_rs}}, {{input2_rs}}, {{input3_rs}}];
+
+fn solve(part: i32, input: &str) -> String {
+    match part {
+        1 => unimplemented!("part 1 ({} lines of input)", input.lines().count()),
+        2 => unimplemented!("part 2"),
+        3 => unimplemented!("part 3"),
+        _ => panic!("no part {part}"),
+    }
+}
+
+fn main() {
+    let part: i32 = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("part must be 1-3"))
+        .unwrap_or(1);
+
+    let path = std::env::var("EC_INPUT").unwrap_or_else(|_| INPUTS[part as usize - 1].to_string());
+    let input = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+
+    println!("{}", solve(part, &input));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+{{#samples}}
+    #[test]
+    fn sample_part{{part}}_{{index}}() {
+        let input = {{sample_rs}};
+        assert_eq!(solve({{part}}, input), {{expected_rs}});
+    }
+{{/samples}}}